@@ -14,9 +14,13 @@ library_config = toml.load("./library_config.toml")
 
 async def config(server):
     
-    medialibrary.tmdb_init(library_config["tmdb"]["key"], library_config["tmdb"]["language"])
+    medialibrary.tmdb_init(library_config["tmdb"]["key"], library_config["tmdb"]["language"],
+                           library_config["tmdb"].get("api_url"), library_config["tmdb"].get("image_url"),
+                           library_config["tmdb"].get("token"))
+    if "cache" in library_config["tmdb"]:
+        medialibrary.tmdb_cache(library_config["tmdb"]["cache"])
 
-    server.create_user_data("medialib", Library(library_config["db"], library_config["rsc"]))
+    server.create_user_data("medialib", Library(library_config["db"], library_config["rsc"], library_config.get("languages")))
     server.create_user_data("executor", Executor())
 
     s = server.config
@@ -187,6 +191,7 @@ class MovieHandler:
             movie.set_videos()
             movie.set_persons()
             movie.set_trailers()
+            movie.set_production()
             return movie.json().encode()
         else:
             return library.movies(user["name"]).json_results().encode()
@@ -208,6 +213,7 @@ class TvHandler:
             episode.set_tv()
             episode.set_season()
             episode.set_videos()
+            episode.set_persons()
             return episode.json().encode()
 
         elif season_number:
@@ -222,6 +228,7 @@ class TvHandler:
             tv.set_seasons()
             tv.set_persons()
             tv.set_trailers()
+            tv.set_production()
             return tv.json().encode()
 
         else:
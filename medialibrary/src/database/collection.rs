@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
-use crate::library::collection::{Collection, CollectionResult};
+use crate::library::collection::{Collection, CollectionPart, CollectionResult};
+use crate::rustmdb::model::MovieCollection;
 
 use super::SqlLibrary;
 use super::{Error, generate_sql};
@@ -34,13 +35,17 @@ impl SqlLibrary{
                 description,
                 creator,
                 creation_date,
-                poster_path) values (?1, ?2, ?3, ?4, ?5, ?6)",
+                poster_path,
+                kind,
+                tmdb_id) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
             &[&collection.id.to_string(), 
                     &collection.name,
                     &collection.description,
                     &collection.creator,
                     &collection.creation_date,
-                    &collection.poster_path,] 
+                    &collection.poster_path,
+                    &collection.kind,
+                    &collection.tmdb_id.to_string(),] 
         )?;
         let id = m_conn.as_ref().unwrap().last_insert_rowid() as u64;
 
@@ -60,6 +65,146 @@ impl SqlLibrary{
         Ok(())
     }
 
+    pub fn create_official_collection(&self, collection: &MovieCollection) -> Result<(u64, Vec<String>), Error>{
+        let mut m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_mut().unwrap();
+        let tx = conn.transaction()?;
+
+        let mut rsc_path = Vec::new();
+
+        let existing: Option<u64> = {
+            let mut stmt = tx.prepare("SELECT id FROM Collections WHERE kind = 'official' AND tmdb_id = ?1")?;
+            let mut rows = stmt.query_map(&[&collection.id.to_string()], |row| row.get(0))?;
+            rows.next().transpose()?
+        };
+
+        let collection_id = match existing{
+            Some(collection_id) => {
+                tx.execute(
+                    "UPDATE Collections
+                        SET name = ?2, description = ?3, poster_path = ?4
+                        WHERE id = ?1",
+                    &[&collection_id.to_string(),
+                        &collection.name,
+                        collection.overview.as_ref().unwrap_or(&"".to_string()),
+                        collection.poster_path.as_ref().unwrap_or(&"".to_string())]
+                )?;
+                collection_id
+            },
+            None => {
+                tx.execute(
+                    "INSERT INTO Collections (
+                        name,
+                        description,
+                        creator,
+                        creation_date,
+                        poster_path,
+                        kind,
+                        tmdb_id) values (?1, ?2, 'tmdb', datetime('now'), ?3, 'official', ?4)",
+                    &[&collection.name,
+                        collection.overview.as_ref().unwrap_or(&"".to_string()),
+                        collection.poster_path.as_ref().unwrap_or(&"".to_string()),
+                        &collection.id.to_string()]
+                )?;
+                tx.last_insert_rowid() as u64
+            }
+        };
+
+        if let Some(poster_path) = &collection.poster_path{
+            rsc_path.push(poster_path.clone())
+        }
+
+        if let Some(backdrop_path) = &collection.backdrop_path{
+            rsc_path.push(backdrop_path.clone())
+        }
+
+        tx.execute("DELETE FROM CollectionParts
+                        WHERE collection_id=?1", &[&collection_id.to_string()])?;
+
+        for part in &collection.parts{
+            tx.execute(
+                "INSERT OR REPLACE INTO CollectionParts (
+                    collection_id,
+                    movie_id,
+                    title,
+                    release_date,
+                    poster_path) values (?1, ?2, ?3, ?4, ?5)",
+                &[&collection_id.to_string(),
+                    &part.id.to_string(),
+                    &part.title,
+                    part.release_date.as_ref().unwrap_or(&"".to_string()),
+                    part.poster_path.as_ref().unwrap_or(&"".to_string())]
+            )?;
+        }
+
+        tx.execute(
+            "INSERT OR IGNORE INTO MovieCollectionLinks (movie_id, collection_id)
+                SELECT Movies.id, ?1 FROM Movies
+                INNER JOIN CollectionParts ON CollectionParts.movie_id = Movies.id
+                WHERE CollectionParts.collection_id = ?1",
+            &[&collection_id.to_string()]
+        )?;
+
+        tx.commit()?;
+
+        Ok((collection_id, rsc_path))
+    }
+
+    pub fn get_official_collection_id(&self, tmdb_id: u64) -> Result<Option<u64>, Error>{
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare("SELECT id FROM Collections WHERE kind = 'official' AND tmdb_id = ?1")?;
+
+        let rows = stmt.query_map(&[&tmdb_id.to_string()], |row| row.get(0))?;
+        for row in rows{
+            return Ok(Some(row?))
+        }
+        Ok(None)
+    }
+
+    pub fn link_official_collection(&self, collection_id: u64, movie_id: u64)  -> Result<(), Error>{
+        let m_conn = self.conn.lock().unwrap();
+        m_conn.as_ref().unwrap().execute(
+            "INSERT OR IGNORE INTO MovieCollectionLinks (
+                movie_id,
+                collection_id) values (?1, ?2)",
+            &[&movie_id.to_string(), &collection_id.to_string()] 
+        )?;
+        Ok(())
+    }
+
+    pub fn get_collection_parts(&self, collection_id: u64) -> Result<Vec<CollectionPart>, Error>{
+        let sql = "SELECT 
+                            movie_id,
+                            title,
+                            release_date,
+                            poster_path,
+                            EXISTS(SELECT 1 FROM Movies WHERE Movies.id = CollectionParts.movie_id)
+                        FROM CollectionParts
+                        WHERE collection_id = ?1
+                        ORDER BY release_date = '', release_date";
+
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(sql)?;
+
+        let rows = stmt.query_map(&[&collection_id.to_string()], |row| {
+            Ok(CollectionPart{
+                movie_id: row.get(0)?,
+                title: row.get(1)?,
+                release_date: row.get(2)?,
+                poster_path: row.get(3)?,
+                owned: row.get(4)?,
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows{
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
     pub fn add_tv_collection(&self, collection_id: u64, tv_id: u64)  -> Result<(), Error>{
         let m_conn = self.conn.lock().unwrap();
         m_conn.as_ref().unwrap().execute(
@@ -78,7 +223,9 @@ impl SqlLibrary{
                             description,
                             creator,
                             creation_date,
-                            poster_path
+                            poster_path,
+                            kind,
+                            tmdb_id
                         FROM Collections
                         WHERE id= ?1";
         
@@ -96,6 +243,8 @@ impl SqlLibrary{
                 creator: row.get(3)?,
                 creation_date: row.get(4)?,
                 poster_path: row.get(5)?,
+                kind: row.get(6)?,
+                tmdb_id: row.get(7)?,
                 movie: Vec::new(),
                 tv: Vec::new(),
             })
@@ -115,7 +264,8 @@ impl SqlLibrary{
                                                     Collections.name,
                                                     Collections.creator,
                                                     Collections.creation_date,
-                                                    Collections.poster_path
+                                                    Collections.poster_path,
+                                                    Collections.kind
                                                 FROM Collections
                                                 LEFT OUTER JOIN MovieCollectionLinks ON Collections.id = MovieCollectionLinks.collection_id
                                                 LEFT OUTER JOIN TvCollectionLinks ON Collections.id = TvCollectionLinks.collection_id
@@ -133,6 +283,7 @@ impl SqlLibrary{
                 creator: row.get(2)?,
                 creation_date: row.get(3)?,
                 poster_path: row.get(4)?, 
+                kind: row.get(5)?,
                 })
             
         })?;
@@ -151,6 +302,9 @@ impl SqlLibrary{
 
         tx.execute("DELETE FROM Collections
                         WHERE id=?1", &[&collection_id.to_string()])?;
+
+        tx.execute("DELETE FROM CollectionParts
+                        WHERE collection_id=?1", &[&collection_id.to_string()])?;
         
         tx.commit()?;
         
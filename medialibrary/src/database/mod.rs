@@ -7,6 +7,9 @@ mod movie;
 mod tv;
 mod person;
 mod collection;
+mod user;
+mod refresh;
+mod production;
 
 lazy_static! {
     pub static ref DATABASE: Arc<SqlLibrary> = Arc::new(SqlLibrary::new());
@@ -115,6 +118,37 @@ impl SqlLibrary{
                 []
         )?;
 
+        // a file covering several episodes keeps the first in media_id and lists all of them here
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS VideoEpisodes (
+                video_id INTEGER NOT NULL,
+                episode_id INTEGER NOT NULL,
+                ord INTEGER,
+                unique(video_id, episode_id))",
+            [],
+        )?;
+
+        conn.execute("DROP VIEW IF EXISTS EpisodeVideosView",[])?;
+        conn.execute(
+            "CREATE VIEW EpisodeVideosView
+                AS
+                SELECT
+                    id as video_id,
+                    media_id as episode_id,
+                    0 as ord
+                FROM
+                    Videos
+                WHERE media_type = 1 AND media_id IS NOT NULL
+                UNION
+                SELECT
+                    video_id,
+                    episode_id,
+                    ord
+                FROM
+                    VideoEpisodes",
+                []
+        )?;
+
         // Movie Part
 
         conn.execute(
@@ -133,9 +167,15 @@ impl SqlLibrary{
                 tagline TEXT,
                 status TEXT,
                 adult BOOL,
+                imdb_id TEXT,
                 updated TEXT)",
             [],
         )?;
+        add_column(conn, "Movies", "imdb_id", "TEXT DEFAULT ''")?;
+        add_column(conn, "Movies", "runtime", "INTEGER DEFAULT 0")?;
+        add_column(conn, "Movies", "budget", "INTEGER DEFAULT 0")?;
+        add_column(conn, "Movies", "revenue", "INTEGER DEFAULT 0")?;
+        add_column(conn, "Movies", "homepage", "TEXT DEFAULT ''")?;
 
         conn.execute(
             "CREATE TABLE IF NOT EXISTS MovieGenres (
@@ -205,6 +245,17 @@ impl SqlLibrary{
             [],
         )?;
 
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS MovieTranslations (
+                movie_id INTEGER NOT NULL,
+                language TEXT NOT NULL,
+                title TEXT,
+                overview TEXT,
+                tagline TEXT,
+                unique(movie_id,language))",
+            [],
+        )?;
+
         conn.execute("DROP VIEW IF EXISTS MoviesView",[])?;
         conn.execute(
             "CREATE VIEW MoviesView
@@ -223,6 +274,11 @@ impl SqlLibrary{
                     vote_count,
                     tagline,
                     status,
+                    imdb_id,
+                    runtime,
+                    budget,
+                    revenue,
+                    homepage,
                     updated,
                     GROUP_CONCAT(DISTINCT MovieGenres.name) as genres,
                     MAX(Videos.adding) as adding
@@ -294,9 +350,13 @@ impl SqlLibrary{
                 number_of_episodes INTEGER,
                 number_of_seasons INTEGER,
                 episode_run_time INTEGER,
+                imdb_id TEXT,
+                tvdb_id TEXT,
                 updated TEXT)",
             [],
         )?;
+        add_column(conn, "Tvs", "imdb_id", "TEXT DEFAULT ''")?;
+        add_column(conn, "Tvs", "tvdb_id", "TEXT DEFAULT ''")?;
 
         conn.execute(
             "CREATE TABLE IF NOT EXISTS TvGenres (
@@ -357,6 +417,17 @@ impl SqlLibrary{
             [],
         )?;
 
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS TvTranslations (
+                tv_id INTEGER NOT NULL,
+                language TEXT NOT NULL,
+                title TEXT,
+                overview TEXT,
+                tagline TEXT,
+                unique(tv_id,language))",
+            [],
+        )?;
+
         conn.execute(
             "CREATE TABLE IF NOT EXISTS Seasons (
                 id INTEGER PRIMARY KEY NOT NULL,
@@ -383,9 +454,15 @@ impl SqlLibrary{
                 overview TEXT,
                 vote_average FLOAT,
                 vote_count INTEGER,
+                still_path TEXT,
+                imdb_id TEXT,
+                tvdb_id TEXT,
                 updated TEXT)",
             [],
         )?;
+        add_column(conn, "Episodes", "still_path", "TEXT DEFAULT ''")?;
+        add_column(conn, "Episodes", "imdb_id", "TEXT DEFAULT ''")?;
+        add_column(conn, "Episodes", "tvdb_id", "TEXT DEFAULT ''")?;
 
         conn.execute(
             "CREATE TABLE IF NOT EXISTS EpisodeCasts (
@@ -396,6 +473,7 @@ impl SqlLibrary{
                 unique(episode_id,person_id,character))",
             [],
         )?;
+        add_column(conn, "EpisodeCasts", "guest", "INTEGER DEFAULT 0")?;
 
         conn.execute(
             "CREATE TABLE IF NOT EXISTS EpisodeCrews (
@@ -436,6 +514,8 @@ impl SqlLibrary{
                     number_of_episodes,
                     number_of_seasons,
                     episode_run_time,
+                    Tvs.imdb_id as imdb_id,
+                    Tvs.tvdb_id as tvdb_id,
                     Tvs.updated as updated,
                     GROUP_CONCAT(DISTINCT TvGenres.name) as genres,
                     MAX(Videos.adding) as adding
@@ -444,7 +524,8 @@ impl SqlLibrary{
                 LEFT OUTER JOIN TvGenreLinks ON Tvs.id = TvGenreLinks.tv_id
                 LEFT OUTER JOIN TvGenres ON TvGenreLinks.genre_id = TvGenres.id
                 LEFT OUTER JOIN Episodes ON Tvs.id = Episodes.tv_id
-                INNER JOIN Videos ON Videos.media_id = Episodes.id AND Videos.media_type = 1
+                INNER JOIN EpisodeVideosView ON EpisodeVideosView.episode_id = Episodes.id
+                INNER JOIN Videos ON Videos.id = EpisodeVideosView.video_id
 
                 GROUP BY Tvs.id",
                 []
@@ -466,7 +547,8 @@ impl SqlLibrary{
                 FROM
                     Seasons
                 INNER JOIN Episodes ON Episodes.season_id = Seasons.id
-                INNER JOIN Videos ON Videos.media_id = Episodes.id AND Videos.media_type = 1
+                INNER JOIN EpisodeVideosView ON EpisodeVideosView.episode_id = Episodes.id
+                INNER JOIN Videos ON Videos.id = EpisodeVideosView.video_id
 
                 GROUP BY Seasons.id",
                 []
@@ -486,12 +568,14 @@ impl SqlLibrary{
                     overview,
                     vote_average,
                     vote_count,
+                    still_path,
                     updated
                 FROM
                     Episodes
-                INNER JOIN Videos ON Videos.media_id = Episodes.id AND Videos.media_type = 1
+                INNER JOIN EpisodeVideosView ON EpisodeVideosView.episode_id = Episodes.id
+                INNER JOIN Videos ON Videos.id = EpisodeVideosView.video_id
                 
-                GROUP BY Videos.id",
+                GROUP BY Episodes.id",
                 []
         )?;
 
@@ -542,7 +626,8 @@ impl SqlLibrary{
                     episode_id,
                     ord,
                     name,
-                    profile_path
+                    profile_path,
+                    guest
                 FROM
                     EpisodeCasts
                 LEFT OUTER JOIN Persons ON EpisodeCasts.person_id = Persons.id
@@ -582,10 +667,108 @@ impl SqlLibrary{
                 biography TEXT,
                 popularity FLOAT,
                 place_of_birth TEXT,
-                profile_path TEXT)",
+                profile_path TEXT,
+                imdb_id TEXT,
+                updated TEXT)",
+            []
+        )?;
+        add_column(conn, "Persons", "imdb_id", "TEXT DEFAULT ''")?;
+        add_column(conn, "Persons", "updated", "TEXT DEFAULT ''")?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS PersonCredits (
+                person_id INTEGER NOT NULL,
+                credit_id TEXT NOT NULL,
+                media_type TEXT,
+                media_id INTEGER,
+                title TEXT,
+                original_title TEXT,
+                character TEXT,
+                job TEXT,
+                department TEXT,
+                release_date TEXT,
+                poster_path TEXT,
+                episode_count INTEGER,
+                unique(person_id, credit_id))",
             []
         )?;
 
+        //production
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS Companies (
+                id INTEGER PRIMARY KEY NOT NULL,
+                name TEXT,
+                logo_path TEXT,
+                origin_country TEXT)",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS Networks (
+                id INTEGER PRIMARY KEY NOT NULL,
+                name TEXT,
+                logo_path TEXT,
+                origin_country TEXT)",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS Countries (
+                iso_3166_1 TEXT PRIMARY KEY NOT NULL,
+                name TEXT)",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS Languages (
+                iso_639_1 TEXT PRIMARY KEY NOT NULL,
+                name TEXT)",
+            [],
+        )?;
+
+        for media in ["Movie", "Tv"]{
+            let key = format!("{}_id", media.to_lowercase());
+            conn.execute(
+                &format!("CREATE TABLE IF NOT EXISTS {}CompanyLinks (
+                    {} INTEGER NOT NULL,
+                    company_id INTEGER NOT NULL,
+                    unique({}, company_id))", media, key, key),
+                [],
+            )?;
+
+            conn.execute(
+                &format!("CREATE TABLE IF NOT EXISTS {}CountryLinks (
+                    {} INTEGER NOT NULL,
+                    country TEXT NOT NULL,
+                    unique({}, country))", media, key, key),
+                [],
+            )?;
+
+            conn.execute(
+                &format!("CREATE TABLE IF NOT EXISTS {}LanguageLinks (
+                    {} INTEGER NOT NULL,
+                    language TEXT NOT NULL,
+                    unique({}, language))", media, key, key),
+                [],
+            )?;
+        }
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS TvNetworkLinks (
+                tv_id INTEGER NOT NULL,
+                network_id INTEGER NOT NULL,
+                unique(tv_id, network_id))",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS TvOriginCountries (
+                tv_id INTEGER NOT NULL,
+                country TEXT NOT NULL,
+                unique(tv_id, country))",
+            [],
+        )?;
+
         //keywords
         conn.execute(
             "CREATE TABLE IF NOT EXISTS Keywords (
@@ -595,6 +778,12 @@ impl SqlLibrary{
         )?;
 
         //user
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS UserLanguages (
+                user_name TEXT PRIMARY KEY NOT NULL,
+                language TEXT)",
+            [],
+        )?;
 
         //Person Part
         conn.execute(
@@ -605,15 +794,42 @@ impl SqlLibrary{
                 creator TEXT,
                 creation_date TEXT,
                 poster_path TEXT,
+                kind TEXT DEFAULT 'user',
+                tmdb_id INTEGER DEFAULT 0,
                 unique(name, creator))",
             []
         )?;
+        add_column(conn, "Collections", "kind", "TEXT DEFAULT 'user'")?;
+        add_column(conn, "Collections", "tmdb_id", "INTEGER DEFAULT 0")?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS CollectionParts (
+                collection_id INTEGER NOT NULL,
+                movie_id INTEGER NOT NULL,
+                title TEXT,
+                release_date TEXT,
+                poster_path TEXT,
+                unique(collection_id, movie_id))",
+            []
+        )?;
         
 
         Ok(())
     }
 }
 
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<(), rusqlite::Error>{
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for name in columns{
+        if name? == column{
+            return Ok(())
+        }
+    }
+    conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    Ok(())
+}
+
 pub fn parse_concat<T: FromStr>( row: Option<String>) -> Option<Vec<T>>{
     if let Some(row) = row{
         return Some(row.split(",").map(|s| {
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::library::cast::Cast;
 use crate::library::cast::Crew;
@@ -11,6 +11,7 @@ use super::SqlLibrary;
 use super::generate_sql;
 use super::parse_concat;
 use super::parse_watched;
+use super::production;
 use crate::library::movie::{MovieResult, Movie};
 
 
@@ -39,7 +40,12 @@ impl SqlLibrary{
                 tagline,
                 status,
                 adult,
-                updated) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, datetime('now'))",
+                imdb_id,
+                runtime,
+                budget,
+                revenue,
+                homepage,
+                updated) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, datetime('now'))",
 
             &[
                 &movie.id.to_string(),
@@ -55,9 +61,19 @@ impl SqlLibrary{
                 &movie.vote_count.to_string(),
                 &movie.tagline.as_ref().unwrap_or(&"".to_string()),
                 &movie.status,
-                &movie.adult.to_string()],
+                &movie.adult.to_string(),
+                &movie.imdb_id.as_ref().unwrap_or(&"".to_string()),
+                &movie.runtime.unwrap_or(0).to_string(),
+                &movie.budget.to_string(),
+                &movie.revenue.to_string(),
+                &movie.homepage.as_ref().unwrap_or(&"".to_string())],
         )?;
 
+        for table in ["MovieGenreLinks", "MovieCasts", "MovieCrews", "MovieTrailers", "MovieKeywordLinks", "MovieTranslations"]{
+            tx.execute(&format!("DELETE FROM {}
+                                    WHERE movie_id=?1", table), &[&movie.id.to_string()])?;
+        }
+
         if let Some(backdrop_path) = &movie.backdrop_path{
             rsc_path.push(backdrop_path.clone())
         }
@@ -166,6 +182,29 @@ impl SqlLibrary{
             )?;
         }
 
+        production::delete_links(&tx, "Movie", movie.id)?;
+        production::insert_companies(&tx, "Movie", movie.id, &movie.production_companies, &mut rsc_path)?;
+        production::insert_countries(&tx, "Movie", movie.id, &movie.production_countries)?;
+        production::insert_languages(&tx, "Movie", movie.id, &movie.spoken_languages)?;
+
+        for translation in &movie.translations.translations{
+            tx.execute(
+                "INSERT OR REPLACE INTO MovieTranslations (
+                    movie_id,
+                    language,
+                    title,
+                    overview,
+                    tagline) values (?1, ?2, ?3, ?4, ?5)",
+    
+                &[
+                &movie.id.to_string(),
+                &translation.iso_639_1,
+                &translation.data.title.as_ref().unwrap_or(&"".to_string()),
+                &translation.data.overview.as_ref().unwrap_or(&"".to_string()),
+                &translation.data.tagline.as_ref().unwrap_or(&"".to_string())],
+            )?;
+        }
+
         tx.commit()?;
 
         Ok((person_ids, rsc_path))
@@ -176,22 +215,29 @@ impl SqlLibrary{
                         id,
                         original_title,
                         original_language,
-                        title,
+                        COALESCE(NULLIF(MovieTranslations.title, ''), MoviesView.title),
                         release_date,
-                        overview,
+                        COALESCE(NULLIF(MovieTranslations.overview, ''), MoviesView.overview),
                         popularity,
                         poster_path,
                         backdrop_path,
                         vote_average,
                         vote_count,
-                        tagline,
+                        COALESCE(NULLIF(MovieTranslations.tagline, ''), MoviesView.tagline),
                         status,
                         genres,
                         adding,
                         MovieUserWatched.watched,
-                        updated
+                        updated,
+                        imdb_id,
+                        runtime,
+                        budget,
+                        revenue,
+                        homepage
                         FROM MoviesView
                         LEFT OUTER JOIN MovieUserWatched ON MoviesView.id = MovieUserWatched.movie_id AND MovieUserWatched.user_name = ?1
+                        LEFT OUTER JOIN UserLanguages ON UserLanguages.user_name = ?1
+                        LEFT OUTER JOIN MovieTranslations ON MoviesView.id = MovieTranslations.movie_id AND MovieTranslations.language = UserLanguages.language
                         WHERE id = ?2
                         GROUP BY MoviesView.id";
         let m_conn = self.conn.lock().unwrap();
@@ -219,12 +265,20 @@ impl SqlLibrary{
                 adding: row.get(14)?,
                 watched: parse_watched(row.get(15)?),  
                 updated: row.get(16)?,
+                imdb_id: row.get(17)?,
+                runtime: row.get(18)?,
+                budget: row.get(19)?,
+                revenue: row.get(20)?,
+                homepage: row.get(21)?,
                 video: Vec::new(),
                 cast: Vec::new(),
                 crew: Vec::new(),
                 trailer: Vec::new(),
                 keyword: Vec::new(),
                 collection: Vec::new(),
+                companies: Vec::new(),
+                countries: Vec::new(),
+                languages: Vec::new(),
 
             })
         })?;
@@ -240,14 +294,15 @@ impl SqlLibrary{
                 order_by: &Option<String>, limit: Option<u64>, offset: Option<u64>) -> Result<Vec<MovieResult>, Error>{
         let (sql, param) = generate_sql("SELECT 
                                                     Movies.id, 
-                                                    Movies.title, 
+                                                    COALESCE(NULLIF(MovieTranslations.title, ''), Movies.title), 
                                                     Movies.release_date, 
                                                     Movies.poster_path, 
                                                     Movies.vote_average,
                                                     GROUP_CONCAT(DISTINCT MovieGenres.name),
                                                     MAX(Videos.adding),
                                                     MovieUserWatched.watched,
-                                                    Movies.backdrop_path
+                                                    Movies.backdrop_path,
+                                                    Movies.runtime
                                                 FROM Movies
                                                 INNER JOIN Videos ON Movies.id = Videos.media_id AND Videos.media_type = 0
                                                 LEFT OUTER JOIN MovieGenreLinks ON Movies.id = MovieGenreLinks.movie_id
@@ -255,7 +310,10 @@ impl SqlLibrary{
                                                 LEFT OUTER JOIN MovieCasts ON Movies.id = MovieCasts.movie_id
                                                 LEFT OUTER JOIN MovieCrews ON Movies.id = MovieCrews.movie_id
                                                 LEFT OUTER JOIN MovieCollectionLinks ON Movies.id = MovieCollectionLinks.movie_id
-                                                LEFT OUTER JOIN MovieUserWatched ON Movies.id = MovieUserWatched.movie_id AND MovieUserWatched.user_name = ?1", 
+                                                LEFT OUTER JOIN MovieUserWatched ON Movies.id = MovieUserWatched.movie_id AND MovieUserWatched.user_name = ?1
+                                                LEFT OUTER JOIN UserLanguages ON UserLanguages.user_name = ?1
+                                                LEFT OUTER JOIN MovieTranslations ON Movies.id = MovieTranslations.movie_id AND MovieTranslations.language = UserLanguages.language
+                                                ", 
                                                 &parameters, Some(user), Some("Movies.id"), order_by, limit, offset);
         // println!("sql: {}", &sql);
         let m_conn = self.conn.lock().unwrap();
@@ -274,6 +332,7 @@ impl SqlLibrary{
                 adding: row.get(6)?,
                 watched: parse_watched(row.get(7)?),
                 backdrop_path: row.get(8)?,
+                runtime: row.get(9)?,
             })
             
         })?;
@@ -307,6 +366,7 @@ impl SqlLibrary{
                 ord: row.get(2)?,
                 name: row.get(3)?,
                 profile_path: row.get(4)?,
+                guest: false,
             })
             
         })?;
@@ -473,8 +533,31 @@ impl SqlLibrary{
         tx.execute("DELETE FROM MovieUserWatched
                         WHERE movie_id=?1", &[&movie_id.to_string()])?;
 
+        tx.execute("DELETE FROM MovieTranslations
+                        WHERE movie_id=?1", &[&movie_id.to_string()])?;
+
+        production::delete_links(&tx, "Movie", movie_id)?;
+
         tx.commit()?;
         
         Ok(())
     }
-}
\ No newline at end of file
+
+    pub fn existing_movies(&self, ids: &[u64]) -> Result<HashSet<u64>, Error>{
+        let mut result = HashSet::new();
+        if ids.is_empty(){
+            return Ok(result)
+        }
+        let sql = format!("SELECT id FROM Movies WHERE id IN ({})", vec!["?"; ids.len()].join(","));
+
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+
+        let rows = stmt.query_map(rusqlite::params_from_iter(ids), |row| row.get(0))?;
+        for row in rows{
+            result.insert(row?);
+        }
+        Ok(result)
+    }
+}
@@ -1,5 +1,6 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use crate::library::cast::Credit;
 use crate::library::cast::Person;
 use crate::library::cast::PersonResult;
 use crate::rustmdb;
@@ -29,7 +30,9 @@ impl SqlLibrary{
                 biography,
                 popularity,
                 place_of_birth,
-                profile_path) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                profile_path,
+                imdb_id,
+                updated) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, datetime('now'))",
 
             &[
             &person.id.to_string(),
@@ -41,13 +44,50 @@ impl SqlLibrary{
             &person.biography,
             &person.popularity.to_string(),
             &person.place_of_birth.as_ref().unwrap_or(&"".to_string()),
-            &person.profile_path.as_ref().unwrap_or(&"".to_string())],
+            &person.profile_path.as_ref().unwrap_or(&"".to_string()),
+            &person.imdb_id.as_ref().unwrap_or(&"".to_string())],
         )?;
 
         if let Some(profile_path) = &person.profile_path{
             rsc_path.push(profile_path.clone())
         }
 
+        tx.execute("DELETE FROM PersonCredits
+                        WHERE person_id=?1", &[&person.id.to_string()])?;
+
+        let credits = person.combined_credits.cast.iter().chain(person.combined_credits.crew.iter());
+        for credit in credits{
+            tx.execute(
+                "INSERT OR REPLACE INTO PersonCredits (
+                    person_id,
+                    credit_id,
+                    media_type,
+                    media_id,
+                    title,
+                    original_title,
+                    character,
+                    job,
+                    department,
+                    release_date,
+                    poster_path,
+                    episode_count) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+
+                &[
+                &person.id.to_string(),
+                &credit.credit_id,
+                &credit.media_type,
+                &credit.id.to_string(),
+                credit.title.as_ref().or(credit.name.as_ref()).unwrap_or(&"".to_string()),
+                credit.original_title.as_ref().or(credit.original_name.as_ref()).unwrap_or(&"".to_string()),
+                &credit.character.as_ref().unwrap_or(&"".to_string()),
+                &credit.job.as_ref().unwrap_or(&"".to_string()),
+                &credit.department.as_ref().unwrap_or(&"".to_string()),
+                credit.release_date.as_ref().or(credit.first_air_date.as_ref()).unwrap_or(&"".to_string()),
+                &credit.poster_path.as_ref().unwrap_or(&"".to_string()),
+                &credit.episode_count.unwrap_or(0).to_string()],
+            )?;
+        }
+
         tx.commit()?;
 
         Ok((person_ids, rsc_path))
@@ -64,7 +104,8 @@ impl SqlLibrary{
                             biography,
                             popularity,
                             place_of_birth,
-                            profile_path 
+                            profile_path,
+                            imdb_id
                         FROM Persons
                         WHERE Persons.id = ?1
                         GROUP BY Persons.id ";
@@ -87,10 +128,12 @@ impl SqlLibrary{
                 popularity:row.get(7)?,
                 place_of_birth: row.get(8)?,
                 profile_path: row.get(9)?,
+                imdb_id: row.get(10)?,
                 cast_movie: Vec::new(),
                 crew_movie: Vec::new(),
                 cast_tv:  Vec::new(),
                 crew_tv:  Vec::new(),
+                guest_episodes: Vec::new(),
             })
             
         })?;
@@ -135,6 +178,56 @@ impl SqlLibrary{
         Ok(result)
     }
 
+    pub fn get_person_credits(&self, user: &String, person_id: u64) -> Result<Vec<Credit>, Error>{
+        let sql = "SELECT 
+                            media_type,
+                            media_id,
+                            title,
+                            original_title,
+                            character,
+                            job,
+                            department,
+                            release_date,
+                            poster_path,
+                            episode_count,
+                            CASE media_type
+                                WHEN 'movie' THEN EXISTS(SELECT 1 FROM Movies WHERE Movies.id = PersonCredits.media_id)
+                                ELSE EXISTS(SELECT 1 FROM Tvs WHERE Tvs.id = PersonCredits.media_id)
+                            END
+                        FROM PersonCredits
+                        WHERE person_id = ?1
+                        ORDER BY release_date DESC, title";
+
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(sql)?;
+
+        let rows = stmt.query_map([&person_id.to_string()], |row| {
+            let release_date: String = row.get(7)?;
+            Ok(Credit{
+                user: user.clone(),
+                media_type: row.get(0)?,
+                media_id: row.get(1)?,
+                title: row.get(2)?,
+                original_title: row.get(3)?,
+                character: row.get(4)?,
+                job: row.get(5)?,
+                department: row.get(6)?,
+                year: release_date.get(..4).and_then(|year| year.parse().ok()),
+                release_date,
+                poster_path: row.get(8)?,
+                episode_count: row.get(9)?,
+                owned: row.get(10)?,
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows{
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
     pub fn delete_person(&self, person_id: u64) -> Result<(), Error>{
         let mut m_conn = self.conn.lock().unwrap();
         let conn = m_conn.as_mut().unwrap();
@@ -142,9 +235,30 @@ impl SqlLibrary{
 
         tx.execute("DELETE FROM Persons
                         WHERE id=?1", &[&person_id.to_string()])?;
+
+        tx.execute("DELETE FROM PersonCredits
+                        WHERE person_id=?1", &[&person_id.to_string()])?;
         
         tx.commit()?;
         
         Ok(())
     }
-}
\ No newline at end of file
+
+    pub fn existing_persons(&self, ids: &[u64]) -> Result<HashSet<u64>, Error>{
+        let mut result = HashSet::new();
+        if ids.is_empty(){
+            return Ok(result)
+        }
+        let sql = format!("SELECT id FROM Persons WHERE id IN ({})", vec!["?"; ids.len()].join(","));
+
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+
+        let rows = stmt.query_map(rusqlite::params_from_iter(ids), |row| row.get(0))?;
+        for row in rows{
+            result.insert(row?);
+        }
+        Ok(result)
+    }
+}
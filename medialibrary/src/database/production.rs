@@ -0,0 +1,275 @@
+use rusqlite::Transaction;
+
+use crate::library::production::{Company, Country, Language, Network};
+use crate::rustmdb::model::{Language as TmdbLanguage, Network as TmdbNetwork, ProductionCompanie, ProductionCountrie};
+use super::Error;
+use super::SqlLibrary;
+
+// link tables are named after the media, "Movie" or "Tv", and keyed by movie_id or tv_id
+fn media_key(media: &str) -> String{
+    format!("{}_id", media.to_lowercase())
+}
+
+pub fn insert_companies(tx: &Transaction, media: &str, media_id: u64, companies: &[ProductionCompanie],
+                        rsc_path: &mut Vec<String>) -> Result<(), Error>{
+    for company in companies{
+        tx.execute(
+            "INSERT OR REPLACE INTO Companies (
+                id,
+                name,
+                logo_path,
+                origin_country) values (?1, ?2, ?3, ?4)",
+
+            &[
+            &company.id.to_string(),
+            &company.name,
+            &company.logo_path.as_ref().unwrap_or(&"".to_string()),
+            &company.origin_country],
+        )?;
+
+        tx.execute(
+            &format!("INSERT OR REPLACE INTO {}CompanyLinks (
+                company_id,
+                {}) values (?1, ?2)", media, media_key(media)),
+
+            &[
+            &company.id.to_string(),
+            &media_id.to_string()],
+        )?;
+
+        if let Some(logo_path) = &company.logo_path{
+            rsc_path.push(logo_path.clone())
+        }
+    }
+    Ok(())
+}
+
+pub fn insert_networks(tx: &Transaction, tv_id: u64, networks: &[TmdbNetwork], rsc_path: &mut Vec<String>) -> Result<(), Error>{
+    for network in networks{
+        tx.execute(
+            "INSERT OR REPLACE INTO Networks (
+                id,
+                name,
+                logo_path,
+                origin_country) values (?1, ?2, ?3, ?4)",
+
+            &[
+            &network.id.to_string(),
+            &network.name,
+            &network.logo_path.as_ref().unwrap_or(&"".to_string()),
+            &network.origin_country],
+        )?;
+
+        tx.execute(
+            "INSERT OR REPLACE INTO TvNetworkLinks (
+                network_id,
+                tv_id) values (?1, ?2)",
+
+            &[
+            &network.id.to_string(),
+            &tv_id.to_string()],
+        )?;
+
+        if let Some(logo_path) = &network.logo_path{
+            rsc_path.push(logo_path.clone())
+        }
+    }
+    Ok(())
+}
+
+pub fn insert_countries(tx: &Transaction, media: &str, media_id: u64, countries: &[ProductionCountrie]) -> Result<(), Error>{
+    for country in countries{
+        tx.execute(
+            "INSERT OR REPLACE INTO Countries (
+                iso_3166_1,
+                name) values (?1, ?2)",
+
+            &[
+            &country.iso_3166_1,
+            &country.name],
+        )?;
+
+        tx.execute(
+            &format!("INSERT OR REPLACE INTO {}CountryLinks (
+                country,
+                {}) values (?1, ?2)", media, media_key(media)),
+
+            &[
+            &country.iso_3166_1,
+            &media_id.to_string()],
+        )?;
+    }
+    Ok(())
+}
+
+// tmdb only gives the codes here, the names come from the production countries
+pub fn insert_origin_countries(tx: &Transaction, tv_id: u64, countries: &[String]) -> Result<(), Error>{
+    for country in countries{
+        tx.execute(
+            "INSERT OR REPLACE INTO TvOriginCountries (
+                country,
+                tv_id) values (?1, ?2)",
+
+            &[
+            country,
+            &tv_id.to_string()],
+        )?;
+    }
+    Ok(())
+}
+
+pub fn insert_languages(tx: &Transaction, media: &str, media_id: u64, languages: &[TmdbLanguage]) -> Result<(), Error>{
+    for language in languages{
+        tx.execute(
+            "INSERT OR REPLACE INTO Languages (
+                iso_639_1,
+                name) values (?1, ?2)",
+
+            &[
+            &language.iso_639_1,
+            &language.name],
+        )?;
+
+        tx.execute(
+            &format!("INSERT OR REPLACE INTO {}LanguageLinks (
+                language,
+                {}) values (?1, ?2)", media, media_key(media)),
+
+            &[
+            &language.iso_639_1,
+            &media_id.to_string()],
+        )?;
+    }
+    Ok(())
+}
+
+impl SqlLibrary{
+    pub fn get_companies(&self, media: &str, media_id: u64) -> Result<Vec<Company>, Error>{
+        let sql = format!("SELECT
+                            id,
+                            name,
+                            logo_path,
+                            origin_country
+                        FROM {}CompanyLinks
+                        INNER JOIN Companies ON {}CompanyLinks.company_id = Companies.id
+                        WHERE {} = ?", media, media, media_key(media));
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+
+        let rows = stmt.query_map(&[&media_id.to_string()], |row| {
+            Ok(Company{
+                id: row.get(0)?,
+                name: row.get(1)?,
+                logo_path: row.get(2)?,
+                origin_country: row.get(3)?,
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows{
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    pub fn get_networks(&self, tv_id: u64) -> Result<Vec<Network>, Error>{
+        let sql = "SELECT
+                            id,
+                            name,
+                            logo_path,
+                            origin_country
+                        FROM TvNetworkLinks
+                        INNER JOIN Networks ON TvNetworkLinks.network_id = Networks.id
+                        WHERE tv_id = ?";
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(sql)?;
+
+        let rows = stmt.query_map(&[&tv_id.to_string()], |row| {
+            Ok(Network{
+                id: row.get(0)?,
+                name: row.get(1)?,
+                logo_path: row.get(2)?,
+                origin_country: row.get(3)?,
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows{
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    pub fn get_countries(&self, media: &str, media_id: u64) -> Result<Vec<Country>, Error>{
+        self.countries(&format!("{}CountryLinks", media), &media_key(media), media_id)
+    }
+
+    pub fn get_origin_countries(&self, tv_id: u64) -> Result<Vec<Country>, Error>{
+        self.countries("TvOriginCountries", "tv_id", tv_id)
+    }
+
+    fn countries(&self, table: &str, key: &str, media_id: u64) -> Result<Vec<Country>, Error>{
+        let sql = format!("SELECT
+                            country,
+                            COALESCE(Countries.name, '')
+                        FROM {}
+                        LEFT OUTER JOIN Countries ON {}.country = Countries.iso_3166_1
+                        WHERE {} = ?", table, table, key);
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+
+        let rows = stmt.query_map(&[&media_id.to_string()], |row| {
+            Ok(Country{
+                iso_3166_1: row.get(0)?,
+                name: row.get(1)?,
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows{
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    pub fn get_languages(&self, media: &str, media_id: u64) -> Result<Vec<Language>, Error>{
+        let sql = format!("SELECT
+                            iso_639_1,
+                            name
+                        FROM {}LanguageLinks
+                        INNER JOIN Languages ON {}LanguageLinks.language = Languages.iso_639_1
+                        WHERE {} = ?", media, media, media_key(media));
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+
+        let rows = stmt.query_map(&[&media_id.to_string()], |row| {
+            Ok(Language{
+                iso_639_1: row.get(0)?,
+                name: row.get(1)?,
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows{
+            result.push(row?);
+        }
+        Ok(result)
+    }
+}
+
+pub fn delete_links(tx: &Transaction, media: &str, media_id: u64) -> Result<(), Error>{
+    let mut tables = vec![format!("{}CompanyLinks", media), format!("{}CountryLinks", media), format!("{}LanguageLinks", media)];
+    if media == "Tv"{
+        tables.push("TvNetworkLinks".to_string());
+        tables.push("TvOriginCountries".to_string());
+    }
+    for table in tables{
+        tx.execute(&format!("DELETE FROM {}
+                                WHERE {}=?1", table, media_key(media)), &[&media_id.to_string()])?;
+    }
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+
+use super::Error;
+use super::SqlLibrary;
+
+
+impl SqlLibrary{
+    // every tmdb id of Movies, Tvs or Persons with whether it was updated more than max_age days ago,
+    // local ids from nfo files start at below_id and skeletons wait for their tmdb import
+    pub fn updated_ages(&self, table: &str, max_age: u64, below_id: u64) -> Result<Vec<(u64, bool)>, Error>{
+        let sql = format!("SELECT id, updated < datetime('now', ?1) FROM {} WHERE id < ?2 AND IFNULL(updated, '') != ''", table);
+
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+
+        let rows = stmt.query_map([format!("-{} days", max_age), below_id.to_string()], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut result = Vec::new();
+        for row in rows{
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    // rows written from an nfo carry no update time, the next tmdb import replaces them
+    pub fn mark_skeletons(&self, table: &str, ids: &[u64]) -> Result<(), Error>{
+        if ids.is_empty(){
+            return Ok(())
+        }
+        let sql = format!("UPDATE {} SET updated = '' WHERE id IN ({})", table, vec!["?"; ids.len()].join(","));
+
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        conn.execute(&sql, rusqlite::params_from_iter(ids))?;
+        Ok(())
+    }
+
+    pub fn skeletons(&self, table: &str, ids: &[u64]) -> Result<HashSet<u64>, Error>{
+        let mut result = HashSet::new();
+        if ids.is_empty(){
+            return Ok(result)
+        }
+        let sql = format!("SELECT id FROM {} WHERE IFNULL(updated, '') = '' AND id IN ({})", table, vec!["?"; ids.len()].join(","));
+
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+
+        let rows = stmt.query_map(rusqlite::params_from_iter(ids), |row| row.get(0))?;
+        for row in rows{
+            result.insert(row?);
+        }
+        Ok(result)
+    }
+
+    // never fetched from tmdb or fetched more than max_age days ago
+    pub fn is_stale(&self, table: &str, id: u64, max_age: u64) -> Result<bool, Error>{
+        let sql = format!("SELECT IFNULL(updated, '') < datetime('now', ?2) FROM {} WHERE id = ?1", table);
+
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+
+        let rows = stmt.query_map([id.to_string(), format!("-{} days", max_age)], |row| row.get(0))?;
+        for row in rows{
+            return Ok(row?)
+        }
+        Ok(true)
+    }
+
+    pub fn oldest_update(&self, table: &str, within_days: u64, below_id: u64) -> Result<Option<String>, Error>{
+        let sql = format!("SELECT MIN(updated) FROM {} WHERE updated >= datetime('now', ?1) AND id < ?2", table);
+
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+
+        let rows = stmt.query_map([format!("-{} days", within_days), below_id.to_string()], |row| row.get(0))?;
+        for row in rows{
+            return Ok(row?)
+        }
+        Ok(None)
+    }
+
+    pub fn episode_seasons(&self, tv_id: u64) -> Result<Vec<u64>, Error>{
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare("SELECT DISTINCT season_number FROM Episodes WHERE tv_id = ?1 ORDER BY season_number")?;
+
+        let rows = stmt.query_map([&tv_id.to_string()], |row| row.get(0))?;
+
+        let mut result = Vec::new();
+        for row in rows{
+            result.push(row?);
+        }
+        Ok(result)
+    }
+}
@@ -1,4 +1,6 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::Transaction;
 
 use crate::database::parse_watched;
 use crate::library::cast::Cast;
@@ -11,10 +13,11 @@ use crate::library::tv::Tv;
 use crate::library::tv::TvResult;
 use crate::library::tv::Season;
 use crate::rustmdb;
-use super::Error;
+use super::{Error, ErrorKind};
 use super::SqlLibrary;
 use super::generate_sql;
 use super::parse_concat;
+use super::production;
 
 
 impl SqlLibrary{
@@ -45,7 +48,9 @@ impl SqlLibrary{
                 number_of_episodes,
                 number_of_seasons,
                 episode_run_time,
-                updated) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, datetime('now'))",
+                imdb_id,
+                tvdb_id,
+                updated) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, datetime('now'))",
 
             &[
             &tv.id.to_string(),
@@ -63,9 +68,16 @@ impl SqlLibrary{
             &tv.in_production.to_string(),
             &tv.number_of_episodes.to_string(),
             &tv.number_of_seasons.to_string(),
-            &tv.episode_run_time.get(0).unwrap_or(&0).to_string()],
+            &tv.episode_run_time.get(0).unwrap_or(&0).to_string(),
+            &tv.external_ids.imdb_id.as_ref().unwrap_or(&"".to_string()),
+            &tv.external_ids.tvdb_id.map(|id| id.to_string()).unwrap_or_default()],
         )?;
 
+        for table in ["TvGenreLinks", "TvCasts", "TvCrews", "TvTrailers", "TvKeywordLinks", "TvTranslations"]{
+            tx.execute(&format!("DELETE FROM {}
+                                    WHERE tv_id=?1", table), &[&tv.id.to_string()])?;
+        }
+
         for season in &tv.seasons{
             // println!("season {}", season.season_number);
             tx.execute(
@@ -221,89 +233,181 @@ impl SqlLibrary{
             )?;
         }
 
+        production::delete_links(&tx, "Tv", tv.id)?;
+        production::insert_companies(&tx, "Tv", tv.id, &tv.production_companies, &mut rsc_path)?;
+        production::insert_networks(&tx, tv.id, &tv.networks, &mut rsc_path)?;
+        production::insert_countries(&tx, "Tv", tv.id, &tv.production_countries)?;
+        production::insert_origin_countries(&tx, tv.id, &tv.origin_country)?;
+        production::insert_languages(&tx, "Tv", tv.id, &tv.spoken_languages)?;
+
+        for translation in &tv.translations.translations{
+            tx.execute(
+                "INSERT OR REPLACE INTO TvTranslations (
+                    tv_id,
+                    language,
+                    title,
+                    overview,
+                    tagline) values (?1, ?2, ?3, ?4, ?5)",
+    
+                &[
+                &tv.id.to_string(),
+                &translation.iso_639_1,
+                &translation.data.name.as_ref().unwrap_or(&"".to_string()),
+                &translation.data.overview.as_ref().unwrap_or(&"".to_string()),
+                &translation.data.tagline.as_ref().unwrap_or(&"".to_string())],
+            )?;
+        }
+
         tx.commit()?;
 
         Ok((person_ids, rsc_path))
     }
 
     pub fn create_episode(&self, tv_id: u64, episode: &rustmdb::model::TvEpisode) -> Result<(Vec<u64>, Vec<String>), Error>{
-        let season_id = self.get_season_id(tv_id, episode.season_number).unwrap().unwrap();
-        
+        let season_id = match self.get_season_id(tv_id, episode.season_number)?{
+            Some(season_id) => season_id,
+            None => return Err(Error::new(ErrorKind::Unknwon, format!("no season {} stored", episode.season_number),
+                                          &format!("create_episode tv {}", tv_id))),
+        };
+
         let mut m_conn = self.conn.lock().unwrap();
         let conn = m_conn.as_mut().unwrap();
         let tx = conn.transaction()?;
 
         let mut person_ids = Vec::new();
-        let rsc_path = Vec::new();
-        
+        let mut rsc_path = Vec::new();
+
+        insert_episode(&tx, season_id, tv_id, episode, &mut person_ids, &mut rsc_path)?;
+
+        tx.commit()?;
+
+        Ok((person_ids, rsc_path))
+    }
+
+    pub fn create_season(&self, tv_id: u64, season: &rustmdb::model::TvSeason) -> Result<(Vec<u64>, Vec<String>), Error>{
+        let mut m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_mut().unwrap();
+        let tx = conn.transaction()?;
+
+        let mut person_ids = Vec::new();
+        let mut rsc_path = Vec::new();
+
         tx.execute(
-            "INSERT OR REPLACE INTO Episodes (
+            "INSERT OR REPLACE INTO Seasons (
                 id,
-                season_id,
                 tv_id,
                 season_number,
-                episode_number,
-                release_date,
+                episode_count,
                 title,
                 overview,
-                vote_average,
-                vote_count,
-                updated) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, datetime('now'))",
+                poster_path,
+                release_date,
+                updated) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))",
 
             &[
-            &episode.id.to_string(),
-            &season_id.to_string(),
+            &season.id.to_string(),
             &tv_id.to_string(),
-            &episode.season_number.to_string(),
-            &episode.episode_number.to_string(),
-            &episode.air_date,
-            &episode.name,
-            &episode.overview.as_ref().unwrap_or(&"".to_string()),
-            &episode.vote_average.to_string(),
-            &episode.vote_count.to_string()],
+            &season.season_number.to_string(),
+            &season.episodes.len().to_string(),
+            &season.name,
+            &season.overview.as_ref().unwrap_or(&"".to_string()),
+            &season.poster_path.as_ref().unwrap_or(&"".to_string()),
+            &season.air_date.as_ref().unwrap_or(&"".to_string()),],
         )?;
 
-        for cast in &episode.credits.cast{
-            tx.execute(
-                "INSERT OR REPLACE INTO EpisodeCasts (
-                    person_id,
-                    episode_id,
-                    character,
-                    ord) values (?1, ?2, ?3, ?4)",
-    
-                &[
-                &cast.id.to_string(),
-                &episode.id.to_string(),
-                &cast.character.as_ref().unwrap_or(&"".to_string()), 
-                &cast.order.to_string()],
-            )?;
+        if let Some(poster_path) = &season.poster_path{
+            rsc_path.push(poster_path.clone())
+        }
 
-            person_ids.push(cast.id)
+        for episode in &season.tv_episodes(){
+            insert_episode(&tx, season.id, tv_id, episode, &mut person_ids, &mut rsc_path)?;
         }
 
-        for crew in &episode.credits.crew{
-            if !(crew.job == "Screenplay" ||  crew.job == "Director" || crew.job == "Producer"){
-                continue
-            }
-    
-            tx.execute(
-                "INSERT OR REPLACE INTO EpisodeCrews (
-                    person_id,
-                    episode_id,
-                    job) values (?1, ?2, ?3)",
+        tx.commit()?;
+
+        Ok((person_ids, rsc_path))
+    }
+
+    pub fn get_episode_id(&self, tv_id: u64, season_number: u64, episode_number: u64) -> Result<Option<u64>, Error> {
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id from Episodes
+             WHERE tv_id = ?1 and season_number = ?2 and episode_number = ?3",
+        )?;
     
-                &[
-                &crew.id.to_string(),
-                &episode.id.to_string(), 
-                &crew.job.to_string()],
-            )?;
+        let rows = stmt.query_map(&[&tv_id.to_string(), &season_number.to_string(), &episode_number.to_string()], |row| row.get(0))?;
+        for row in rows{
+            return Ok(Some(row?))
+        }
+        Ok(None)
+    }
 
-            person_ids.push(crew.id)
+    // no main cast and no external id yet, as stored from a season
+    pub fn episode_lacks_details(&self, episode_id: u64) -> Result<bool, Error> {
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT NOT EXISTS(SELECT 1 FROM EpisodeCasts WHERE episode_id = Episodes.id AND IFNULL(guest, 0) = 0)
+                    OR (IFNULL(imdb_id, '') = '' AND IFNULL(tvdb_id, '') = '')
+             FROM Episodes WHERE id = ?1",
+        )?;
+
+        let rows = stmt.query_map(&[&episode_id.to_string()], |row| row.get(0))?;
+        for row in rows{
+            return Ok(row?)
         }
+        Ok(true)
+    }
 
-        tx.commit()?;
+    // specials are left out, they are not part of an absolute count
+    pub fn get_season_episode_counts(&self, tv_id: u64) -> Result<Vec<(u64, u64)>, Error> {
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT season_number, episode_count from Seasons
+             WHERE tv_id = ?1 and season_number > 0
+             ORDER BY season_number",
+        )?;
 
-        Ok((person_ids, rsc_path))
+        let rows = stmt.query_map(&[&tv_id.to_string()], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        let mut result = Vec::new();
+        for row in rows{
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    pub fn get_episode_by_date(&self, tv_id: u64, date: &str) -> Result<Option<(u64, u64)>, Error> {
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT season_number, episode_number from Episodes
+             WHERE tv_id = ?1 and release_date = ?2
+             ORDER BY season_number, episode_number",
+        )?;
+
+        let rows = stmt.query_map(&[&tv_id.to_string(), date], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        for row in rows{
+            return Ok(Some(row?))
+        }
+        Ok(None)
+    }
+
+    pub fn get_season_dates(&self, tv_id: u64) -> Result<Vec<(u64, String)>, Error> {
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT season_number, COALESCE(release_date, '') from Seasons
+             WHERE tv_id = ?1",
+        )?;
+
+        let rows = stmt.query_map(&[&tv_id.to_string()], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        let mut result = Vec::new();
+        for row in rows{
+            result.push(row?);
+        }
+        Ok(result)
     }
 
     pub fn get_season_id(&self, tv_id: u64, season_number: u64) -> Result<Option<u64>, Error> {
@@ -328,9 +432,9 @@ impl SqlLibrary{
                             TvsView.id, 
                             TvsView.original_title, 
                             TvsView.original_language, 
-                            TvsView.title, 
+                            COALESCE(NULLIF(TvTranslations.title, ''), TvsView.title), 
                             TvsView.release_date, 
-                            TvsView.overview, 
+                            COALESCE(NULLIF(TvTranslations.overview, ''), TvsView.overview), 
                             TvsView.popularity, 
                             TvsView.poster_path, 
                             TvsView.backdrop_path, 
@@ -343,11 +447,16 @@ impl SqlLibrary{
                             TvsView.episode_run_time, 
                             TvsView.adding,
                             MIN(COALESCE(EpisodesUserWatched.watched, 0)),
-                            TvsView.updated
+                            TvsView.updated,
+                            TvsView.imdb_id,
+                            TvsView.tvdb_id
                         FROM TvsView
                         LEFT OUTER JOIN Episodes ON TvsView.id = Episodes.tv_id
                         LEFT OUTER JOIN EpisodesUserWatched ON Episodes.id = EpisodesUserWatched.episode_id AND EpisodesUserWatched.user_name = ?1
-                        WHERE TvsView.id = ?2";
+                        LEFT OUTER JOIN UserLanguages ON UserLanguages.user_name = ?1
+                        LEFT OUTER JOIN TvTranslations ON TvsView.id = TvTranslations.tv_id AND TvTranslations.language = UserLanguages.language
+                        WHERE TvsView.id = ?2
+                        GROUP BY TvsView.id";
 
         let m_conn = self.conn.lock().unwrap();
         let conn = m_conn.as_ref().unwrap();
@@ -374,12 +483,20 @@ impl SqlLibrary{
                 adding: row.get(16)?,
                 watched: parse_watched(row.get(17)?),
                 updated: row.get(18)?,
+                imdb_id: row.get(19)?,
+                tvdb_id: row.get(20)?,
                 seasons: Vec::new(),
                 cast: Vec::new(),
                 crew: Vec::new(),
                 trailer: Vec::new(),
                 keyword: Vec::new(),
                 collection: Vec::new(),
+                companies: Vec::new(),
+                networks: Vec::new(),
+                countries: Vec::new(),
+                origin_countries: Vec::new(),
+                languages: Vec::new(),
+                creators: Vec::new(),
             })
         })?;
 
@@ -394,7 +511,7 @@ impl SqlLibrary{
                     order_by: &Option<String>, limit: Option<u64>, offset: Option<u64>) -> Result<Vec<TvResult>, Error>{
         let (sql, param) = generate_sql("SELECT 
                                                     Tvs.id,
-                                                    Tvs.title,
+                                                    COALESCE(NULLIF(TvTranslations.title, ''), Tvs.title),
                                                     Tvs.release_date,
                                                     Tvs.poster_path,
                                                     Tvs.vote_average,
@@ -404,13 +521,16 @@ impl SqlLibrary{
                                                     Tvs.backdrop_path
                                                 FROM Tvs
                                                 LEFT OUTER JOIN Episodes ON Tvs.id = Episodes.tv_id
-                                                INNER JOIN Videos ON Videos.media_id = Episodes.id AND Videos.media_type = 1
+                                                INNER JOIN EpisodeVideosView ON EpisodeVideosView.episode_id = Episodes.id
+                                                INNER JOIN Videos ON Videos.id = EpisodeVideosView.video_id
                                                 LEFT OUTER JOIN TvGenreLinks ON Tvs.id = TvGenreLinks.tv_id
                                                 LEFT OUTER JOIN TvGenres ON TvGenreLinks.genre_id = TvGenres.id
                                                 LEFT OUTER JOIN TvCasts ON Tvs.id = TvCasts.tv_id
                                                 LEFT OUTER JOIN TvCrews ON Tvs.id = TvCrews.tv_id
                                                 LEFT OUTER JOIN TvCollectionLinks ON Tvs.id = TvCollectionLinks.tv_id
                                                 LEFT OUTER JOIN EpisodesUserWatched ON Episodes.id = EpisodesUserWatched.episode_id AND EpisodesUserWatched.user_name = ?1
+                                                LEFT OUTER JOIN UserLanguages ON UserLanguages.user_name = ?1
+                                                LEFT OUTER JOIN TvTranslations ON Tvs.id = TvTranslations.tv_id AND TvTranslations.language = UserLanguages.language
                                                 ", parameters, Some(user), Some("Tvs.id"), order_by, limit, offset);
 
         //println!("sql: {}", &sql);
@@ -506,9 +626,13 @@ impl SqlLibrary{
                                                     EpisodesUserWatched.watched,
                                                     Episodes.updated,
                                                     Tvs.title,
-                                                    Tvs.poster_path
+                                                    Tvs.poster_path,
+                                                    Episodes.imdb_id,
+                                                    Episodes.tvdb_id,
+                                                    Episodes.still_path
                                                 FROM Episodes
-                                                INNER JOIN Videos ON Videos.media_id = Episodes.id AND Videos.media_type = 1
+                                                INNER JOIN EpisodeVideosView ON EpisodeVideosView.episode_id = Episodes.id
+                                                INNER JOIN Videos ON Videos.id = EpisodeVideosView.video_id
                                                 LEFT OUTER JOIN Tvs ON Episodes.tv_id = Tvs.id
                                                 LEFT OUTER JOIN EpisodeCasts ON Episodes.id = EpisodeCasts.episode_id
                                                 LEFT OUTER JOIN EpisodeCrews ON Episodes.id = EpisodeCrews.episode_id
@@ -535,6 +659,9 @@ impl SqlLibrary{
                 updated: row.get(10)?,
                 tv_title: row.get(11)?,
                 poster_path: row.get(12)?,
+                imdb_id: row.get(13)?,
+                tvdb_id: row.get(14)?,
+                still_path: row.get(15)?,
                 tv: None,
                 season: None,
                 video: Vec::new(),
@@ -583,6 +710,7 @@ impl SqlLibrary{
                 ord: row.get(2)?,
                 name: row.get(3)?,
                 profile_path: row.get(4)?,
+                guest: false,
             })
             
         })?;
@@ -631,9 +759,11 @@ impl SqlLibrary{
                             character,
                             ord,
                             name,
-                            profile_path
+                            profile_path,
+                            guest
                         FROM EpisodeCastsView
-                        WHERE episode_id = ?1";
+                        WHERE episode_id = ?1
+                        ORDER BY guest, ord";
         //println!("sql: {}", &sql);
         let m_conn = self.conn.lock().unwrap();
         let conn = m_conn.as_ref().unwrap();
@@ -647,6 +777,7 @@ impl SqlLibrary{
                 ord: row.get(2)?,
                 name: row.get(3)?,
                 profile_path: row.get(4)?,
+                guest: row.get(5)?,
             })
             
         })?;
@@ -808,10 +939,24 @@ impl SqlLibrary{
 
         tx.execute("DELETE FROM TvCrews
                         WHERE tv_id=?1", &[&tv_id.to_string()])?;
+
+        tx.execute("DELETE FROM TvTranslations
+                        WHERE tv_id=?1", &[&tv_id.to_string()])?;
+
+        production::delete_links(&tx, "Tv", tv_id)?;
         
         tx.execute("DELETE FROM Seasons
                         WHERE tv_id=?1", &[&tv_id.to_string()])?;
 
+        tx.execute("DELETE FROM EpisodeCasts
+                        WHERE episode_id IN (SELECT id FROM Episodes WHERE tv_id=?1)", &[&tv_id.to_string()])?;
+
+        tx.execute("DELETE FROM EpisodeCrews
+                        WHERE episode_id IN (SELECT id FROM Episodes WHERE tv_id=?1)", &[&tv_id.to_string()])?;
+
+        tx.execute("DELETE FROM Episodes
+                        WHERE tv_id=?1", &[&tv_id.to_string()])?;
+
         tx.commit()?;
         
         Ok(())
@@ -838,4 +983,150 @@ impl SqlLibrary{
         
         Ok(())
     }
-}
\ No newline at end of file
+
+    pub fn tv_artwork_paths(&self) -> Result<Vec<String>, Error>{
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT poster_path FROM Seasons WHERE IFNULL(poster_path, '') <> ''
+             UNION
+             SELECT still_path FROM Episodes WHERE IFNULL(still_path, '') <> ''",
+        )?;
+
+        let rows = stmt.query_map([], |row| row.get(0))?;
+
+        let mut result = Vec::new();
+        for row in rows{
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    pub fn existing_tvs(&self, ids: &[u64]) -> Result<HashSet<u64>, Error>{
+        let mut result = HashSet::new();
+        if ids.is_empty(){
+            return Ok(result)
+        }
+        let sql = format!("SELECT id FROM Tvs WHERE id IN ({})", vec!["?"; ids.len()].join(","));
+
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+
+        let rows = stmt.query_map(rusqlite::params_from_iter(ids), |row| row.get(0))?;
+        for row in rows{
+            result.insert(row?);
+        }
+        Ok(result)
+    }
+}
+
+fn insert_episode(tx: &Transaction, season_id: u64, tv_id: u64, episode: &rustmdb::model::TvEpisode,
+                    person_ids: &mut Vec<u64>, rsc_path: &mut Vec<String>) -> Result<(), Error>{
+    tx.execute(
+        "INSERT OR REPLACE INTO Episodes (
+            id,
+            season_id,
+            tv_id,
+            season_number,
+            episode_number,
+            release_date,
+            title,
+            overview,
+            vote_average,
+            vote_count,
+            still_path,
+            imdb_id,
+            tvdb_id,
+            updated) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11,
+                COALESCE(NULLIF(?12, ''), (SELECT imdb_id FROM Episodes WHERE id = ?1), ''),
+                COALESCE(NULLIF(?13, ''), (SELECT tvdb_id FROM Episodes WHERE id = ?1), ''),
+                datetime('now'))",
+
+        &[
+        &episode.id.to_string(),
+        &season_id.to_string(),
+        &tv_id.to_string(),
+        &episode.season_number.to_string(),
+        &episode.episode_number.to_string(),
+        &episode.air_date,
+        &episode.name,
+        &episode.overview.as_ref().unwrap_or(&"".to_string()),
+        &episode.vote_average.to_string(),
+        &episode.vote_count.to_string(),
+        &episode.still_path.as_ref().unwrap_or(&"".to_string()),
+        &episode.external_ids.imdb_id.as_ref().unwrap_or(&"".to_string()),
+        &episode.external_ids.tvdb_id.map(|id| id.to_string()).unwrap_or_default()],
+    )?;
+
+    // a season payload has no main cast, the one stored from the episode itself stays
+    let guests_only = if episode.credits.cast.is_empty() { " AND guest = 1" } else { "" };
+    tx.execute(&format!("DELETE FROM EpisodeCasts
+                            WHERE episode_id=?1{}", guests_only), &[&episode.id.to_string()])?;
+    tx.execute("DELETE FROM EpisodeCrews
+                    WHERE episode_id=?1", &[&episode.id.to_string()])?;
+
+    if let Some(still_path) = &episode.still_path{
+        rsc_path.push(still_path.clone())
+    }
+
+    for cast in &episode.credits.cast{
+        tx.execute(
+            "INSERT OR REPLACE INTO EpisodeCasts (
+                person_id,
+                episode_id,
+                character,
+                ord) values (?1, ?2, ?3, ?4)",
+
+            &[
+            &cast.id.to_string(),
+            &episode.id.to_string(),
+            &cast.character.as_ref().unwrap_or(&"".to_string()), 
+            &cast.order.to_string()],
+        )?;
+
+        person_ids.push(cast.id)
+    }
+
+    // the main cast wins when a guest star is also credited there
+    for guest in &episode.guest_stars{
+        tx.execute(
+            "INSERT OR IGNORE INTO EpisodeCasts (
+                person_id,
+                episode_id,
+                character,
+                ord,
+                guest) values (?1, ?2, ?3, ?4, 1)",
+
+            &[
+            &guest.id.to_string(),
+            &episode.id.to_string(),
+            &guest.character,
+            &guest.order.to_string()],
+        )?;
+
+        person_ids.push(guest.id)
+    }
+
+    for crew in &episode.credits.crew{
+        if !(crew.job == "Screenplay" ||  crew.job == "Director" || crew.job == "Producer"){
+            continue
+        }
+
+        tx.execute(
+            "INSERT OR REPLACE INTO EpisodeCrews (
+                person_id,
+                episode_id,
+                job) values (?1, ?2, ?3)",
+
+            &[
+            &crew.id.to_string(),
+            &episode.id.to_string(), 
+            &crew.job.to_string()],
+        )?;
+
+        person_ids.push(crew.id)
+    }
+
+    Ok(())
+}
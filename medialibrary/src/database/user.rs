@@ -0,0 +1,31 @@
+use super::Error;
+use super::SqlLibrary;
+
+impl SqlLibrary{
+    pub fn set_user_language(&self, user: &String, language: &str) -> Result<(), Error>{
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO UserLanguages (
+                user_name,
+                language) values (?1, ?2)",
+            &[user.as_str(), language],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_user_language(&self, user: &String) -> Result<Option<String>, Error>{
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT language from UserLanguages
+             WHERE user_name = ?1",
+        )?;
+
+        let rows = stmt.query_map(&[user], |row| row.get(0))?;
+        for row in rows{
+            return Ok(Some(row?))
+        }
+        Ok(None)
+    }
+}
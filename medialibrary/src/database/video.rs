@@ -164,6 +164,7 @@ impl SqlLibrary{
                 size: row.get(12)?,
                 subtitles: parse_concat(row.get(13)?).unwrap_or_default(),
                 audios: parse_concat(row.get(14)?).unwrap_or_default(),
+                start: None,
             })
         })?;
 
@@ -186,6 +187,73 @@ impl SqlLibrary{
         Ok(())
     }
 
+    // the first episode stays in media_id, the link rows are only kept for multi episode files
+    pub fn set_video_episodes(&self, video_id: u64, episode_ids: &[u64]) -> Result<(), Error>{
+        let mut m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_mut().unwrap();
+        let tx = conn.transaction()?;
+
+        if let Some(first) = episode_ids.first(){
+            tx.execute(
+                "UPDATE Videos SET media_id = ?1 WHERE id = ?2",
+                &[
+                    &first.to_string(),
+                    &video_id.to_string()],
+            )?;
+        }
+
+        tx.execute("DELETE FROM VideoEpisodes
+                        WHERE video_id=?1", &[&video_id.to_string()])?;
+
+        if episode_ids.len() > 1{
+            for (ord, episode_id) in episode_ids.iter().enumerate(){
+                tx.execute(
+                    "INSERT OR REPLACE INTO VideoEpisodes (
+                        video_id,
+                        episode_id,
+                        ord) values (?1, ?2, ?3)",
+                    &[
+                    &video_id.to_string(),
+                    &episode_id.to_string(),
+                    &ord.to_string()],
+                )?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn get_video_episodes(&self, video_id: u64) -> Result<Vec<u64>, Error>{
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare("SELECT episode_id
+                                        FROM EpisodeVideosView
+                                        WHERE video_id = ?
+                                        ORDER BY ord")?;
+
+        let rows = stmt.query_map(&[&video_id.to_string()], |row| row.get(0))?;
+
+        let mut result = Vec::new();
+        for row in rows{
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    // videos directly inside the folder, not in its subfolders
+    pub fn count_folder_videos(&self, folder: &str) -> Result<u64, Error>{
+        let m_conn = self.conn.lock().unwrap();
+        let conn = m_conn.as_ref().unwrap();
+        let mut stmt = conn.prepare("SELECT COUNT(*)
+                                        FROM Videos
+                                        WHERE substr(path, 1, length(?1) + 1) = ?1 || '/'
+                                        AND instr(substr(path, length(?1) + 2), '/') = 0")?;
+
+        let count = stmt.query_row(&[folder], |row| row.get(0))?;
+        Ok(count)
+    }
+
     pub fn edit_video_path(&self, video_id: u64, path: &str) -> Result<(), Error>{
         let m_conn = self.conn.lock().unwrap();
         let conn = m_conn.as_ref().unwrap();
@@ -233,6 +301,9 @@ impl SqlLibrary{
         tx.execute("DELETE FROM Subtitles
                         WHERE video_id=?1", &[&video_id.to_string()])?;
 
+        tx.execute("DELETE FROM VideoEpisodes
+                        WHERE video_id=?1", &[&video_id.to_string()])?;
+
         tx.commit()?;
         
         Ok(())
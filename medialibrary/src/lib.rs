@@ -24,13 +24,22 @@ impl std::convert::From<database::Error> for PyErr {
     }
 }
 
-use rustmdb::{set_api_key, set_language, Tmdb};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use rustmdb::{set_api_key, set_token, set_language, set_api_url, set_image_url, Tmdb};
+use rustmdb::cache::{set_cache_path, set_ttl, CacheKind};
+use rustmdb::client::{set_rate_limit, set_retries, RATE_LIMIT};
 
 create_exception!(medialibrary, TmdbError, PyException);
+create_exception!(medialibrary, TmdbRateLimitError, TmdbError);
 
 impl std::convert::From<rustmdb::Error> for PyErr {
     fn from(err: rustmdb::Error) -> PyErr {
-        TmdbError::new_err(err.to_string())
+        match err.kind(){
+            rustmdb::ErrorKind::RateLimited => TmdbRateLimitError::new_err(err.to_string()),
+            _ => TmdbError::new_err(err.to_string()),
+        }
     }
 }
 
@@ -42,18 +51,56 @@ impl std::convert::From<library::Error> for PyErr {
     }
 }
 
-#[pyfunction]
-fn tmdb_init(key: &str, lang: &str)  -> PyResult<()> {
+#[pyfunction(api_url = "None", image_url = "None", token = "None")]
+fn tmdb_init(key: &str, lang: &str, api_url: Option<&str>, image_url: Option<&str>, token: Option<&str>)  -> PyResult<()> {
     set_api_key(key);
+    set_token(token);
     set_language(lang);
+    if let Some(api_url) = api_url{
+        set_api_url(api_url);
+    }
+    if let Some(image_url) = image_url{
+        set_image_url(image_url);
+    }
+    Ok(())
+}
+
+// ttls in seconds keyed by cache kind, e.g. {"movie": 86400, "search": 3600}
+#[pyfunction(ttls = "None")]
+fn tmdb_cache(path: &str, ttls: Option<HashMap<String, u64>>) -> PyResult<()> {
+    let mut kinds = Vec::new();
+    for (name, ttl) in ttls.unwrap_or_default(){
+        match CacheKind::from_name(&name){
+            Some(kind) => kinds.push((kind, ttl)),
+            None => return Err(rustmdb::Error::new(rustmdb::ErrorKind::Cache, format!("unknown cache kind {}", name), "tmdb_cache").into()),
+        }
+    }
+    set_cache_path(path);
+    for (kind, ttl) in kinds{
+        set_ttl(kind, Duration::from_secs(ttl));
+    }
+    Ok(())
+}
+
+#[pyfunction(max_requests = "None", period = "None", max_retries = "None", backoff = "None")]
+fn tmdb_rate_limit(max_requests: Option<usize>, period: Option<f64>, max_retries: Option<u32>, backoff: Option<f64>) -> PyResult<()> {
+    let (current_requests, current_period, current_retries, current_backoff) = {
+        let limit = RATE_LIMIT.lock().unwrap();
+        (limit.max_requests, limit.period, limit.max_retries, limit.backoff)
+    };
+    set_rate_limit(max_requests.unwrap_or(current_requests), period.map(Duration::from_secs_f64).unwrap_or(current_period));
+    set_retries(max_retries.unwrap_or(current_retries), backoff.map(Duration::from_secs_f64).unwrap_or(current_backoff));
     Ok(())
 }
 
 #[pymodule]
 fn medialibrary(py: Python, module: &PyModule) -> PyResult<()> {
     module.add("TmdbError", py.get_type::<TmdbError>())?;
+    module.add("TmdbRateLimitError", py.get_type::<TmdbRateLimitError>())?;
     module.add("LibraryError", py.get_type::<LibraryError>())?;
     module.add_function(wrap_pyfunction!(tmdb_init, module)?)?;
+    module.add_function(wrap_pyfunction!(tmdb_cache, module)?)?;
+    module.add_function(wrap_pyfunction!(tmdb_rate_limit, module)?)?;
     module.add_class::<Tmdb>()?;
     module.add_class::<Library>()?;
     module.add_class::<Video>()?;
@@ -4,10 +4,20 @@ use pyo3::prelude::*;
 
 use crate::database::DATABASE;
 
+use super::update_db;
+use super::nfo::LOCAL_ID_BASE;
+use super::provider;
+use super::movie::Movie;
 use super::movie::MovieResult;
 use super::movie::MovieSearch;
+use super::tv::Tv;
 use super::tv::TvResult;
 use super::tv::TvSearch;
+use super::tv::Episode;
+use super::tv::EpisodeSearch;
+
+// days before a filmography is fetched again
+const CREDITS_MAX_AGE: u64 = 30;
 
 #[pyclass]
 #[derive(Debug, Serialize, Clone)]
@@ -54,6 +64,8 @@ pub struct Cast{
     pub name: String,
     #[pyo3(get)]
     pub profile_path: String,
+    #[pyo3(get)]
+    pub guest: bool,
 }
 
 #[pymethods]
@@ -96,6 +108,8 @@ pub struct Person{
     #[pyo3(get)]
     pub profile_path: String,
     #[pyo3(get)]
+    pub imdb_id: String,
+    #[pyo3(get)]
     pub cast_movie: Vec<MovieResult>,
     #[pyo3(get)]
     pub crew_movie: Vec<MovieResult>,
@@ -103,6 +117,8 @@ pub struct Person{
     pub cast_tv: Vec<TvResult>,
     #[pyo3(get)]
     pub crew_tv: Vec<TvResult>,
+    #[pyo3(get)]
+    pub guest_episodes: Vec<Episode>,
 }
 
 #[pymethods]
@@ -116,9 +132,31 @@ impl Person{
     pub fn set_tv(&mut self) -> PyResult<()>{
         self.cast_tv = TvSearch::new(&self.user).cast(self.id)?.results(None, None)?;
         self.crew_tv = TvSearch::new(&self.user).crew(self.id)?.results(None, None)?;
+        self.guest_episodes = EpisodeSearch::new(&self.user).guest(self.id)?.results(None, None)?;
         Ok(())
     }
 
+    #[args(owned = "None")]
+    pub fn filmography(&self, owned: Option<bool>) -> PyResult<Vec<Credit>>{
+        // the person row is written along with its credits, its update time dates them
+        let mut credits = DATABASE.get_person_credits(&self.user, self.id)?;
+        // local ids from nfo files are unknown to tmdb
+        if self.id < LOCAL_ID_BASE && DATABASE.is_stale("Persons", self.id, CREDITS_MAX_AGE)?{
+            update_db::update_person(provider::get(None)?.as_ref(), self.id)?;
+            credits = DATABASE.get_person_credits(&self.user, self.id)?;
+        }
+        if let Some(owned) = owned{
+            credits.retain(|credit| credit.owned == owned);
+        }
+        Ok(credits)
+    }
+
+    #[args(owned = "None")]
+    pub fn filmography_json(&self, owned: Option<bool>) -> PyResult<String>{
+        let list = self.filmography(owned)?;
+        Ok(serde_json::to_string(&list).unwrap())
+    }
+
     pub fn delete(&mut self)  -> PyResult<()>{
         if MovieSearch::new(&self.user).cast(self.id)?.exist()?{
             return Ok(())
@@ -132,6 +170,9 @@ impl Person{
         if TvSearch::new(&self.user).crew(self.id)?.exist()?{
             return Ok(())
         }
+        if EpisodeSearch::new(&self.user).cast(self.id)?.exist()?{
+            return Ok(())
+        }
         
         DATABASE.delete_person(self.id)?;
 
@@ -152,6 +193,61 @@ impl Person{
     }
 }
 
+#[pyclass]
+#[derive(Debug, Serialize, Clone)]
+pub struct Credit{
+    pub user: String,
+    #[pyo3(get)]
+    pub media_type: String,
+    #[pyo3(get)]
+    pub media_id: u64,
+    #[pyo3(get)]
+    pub title: String,
+    #[pyo3(get)]
+    pub original_title: String,
+    #[pyo3(get)]
+    pub character: String,
+    #[pyo3(get)]
+    pub job: String,
+    #[pyo3(get)]
+    pub department: String,
+    #[pyo3(get)]
+    pub release_date: String,
+    #[pyo3(get)]
+    pub year: Option<u64>,
+    #[pyo3(get)]
+    pub poster_path: String,
+    #[pyo3(get)]
+    pub episode_count: u64,
+    #[pyo3(get)]
+    pub owned: bool,
+}
+
+#[pymethods]
+impl Credit{
+    pub fn movie(&self) -> PyResult<Option<Movie>>{
+        if !self.owned || self.media_type != "movie"{
+            return Ok(None)
+        }
+        Ok(DATABASE.get_movie(&self.user, self.media_id)?)
+    }
+
+    pub fn tv(&self) -> PyResult<Option<Tv>>{
+        if !self.owned || self.media_type != "tv"{
+            return Ok(None)
+        }
+        Ok(DATABASE.get_tv(&self.user, self.media_id)?)
+    }
+
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
 #[pyclass]
 #[derive(Debug, Serialize, Clone)]
 pub struct PersonResult{
@@ -27,6 +27,10 @@ pub struct Collection{
     #[pyo3(get)]
     pub poster_path: String,
     #[pyo3(get)]
+    pub kind: String,
+    #[pyo3(get)]
+    pub tmdb_id: u64,
+    #[pyo3(get)]
     pub movie: Vec<MovieResult>,
     #[pyo3(get)]
     pub tv: Vec<TvResult>, 
@@ -63,6 +67,14 @@ impl Collection{
         Ok(DATABASE.add_tv_collection(self.id, tv_id)?)
     }
 
+    pub fn parts(&self) -> PyResult<Vec<CollectionPart>>{
+        Ok(DATABASE.get_collection_parts(self.id)?)
+    }
+
+    pub fn missing(&self) -> PyResult<Vec<CollectionPart>>{
+        Ok(self.parts()?.into_iter().filter(|part| !part.owned).collect())
+    }
+
     pub fn edit_description(&mut self, description: String){
         self.description = description;
     }
@@ -94,6 +106,32 @@ impl Collection{
     }
 }
 
+#[pyclass]
+#[derive(Debug, Serialize, Clone)]
+pub struct CollectionPart{
+    #[pyo3(get)]
+    pub movie_id: u64,
+    #[pyo3(get)]
+    pub title: String,
+    #[pyo3(get)]
+    pub release_date: String,
+    #[pyo3(get)]
+    pub poster_path: String,
+    #[pyo3(get)]
+    pub owned: bool,
+}
+
+#[pymethods]
+impl CollectionPart{
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
 #[pyclass]
 #[derive(Debug, Serialize, Clone)]
 pub struct CollectionResult{
@@ -109,6 +147,8 @@ pub struct CollectionResult{
     pub creation_date: String,
     #[pyo3(get)]
     pub poster_path: String,
+    #[pyo3(get)]
+    pub kind: String,
 }
 
 #[pymethods]
@@ -158,6 +198,10 @@ impl CollectionSearch{
         self.find("Collections.creator", "=", Some(self.user.clone()))
     }
 
+    pub fn official(&mut self) -> PyResult<CollectionSearch>{
+        self.find("Collections.kind", "=", Some("official".to_string()))
+    }
+
     pub fn find(&mut self, column: &str, operator: &str, value: Option<String>) -> PyResult<CollectionSearch>{
         if let Some(value) = value {
             self.parameters.insert(column.to_string(), Some((operator.to_string(), value)));
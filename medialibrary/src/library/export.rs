@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+use regex::Regex;
+
+use super::nfo::{self, Nfo};
+use super::video::{Video, VideoSearch};
+use super::{RSCPATH, rsc};
+
+#[pyclass]
+#[derive(Debug, Clone, Default)]
+pub struct ExportReport{
+    #[pyo3(get)]
+    pub written: Vec<String>,
+    #[pyo3(get)]
+    pub skipped: Vec<String>,
+    #[pyo3(get)]
+    pub errors: Vec<String>,
+}
+
+#[pymethods]
+impl ExportReport{
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+lazy_static! {
+    static ref SEASON_FOLDER: Regex = Regex::new(r"(?i)^(season|saison|staffel|series)[ ._-]*\d+$|^s\d+$|^specials$").unwrap();
+}
+
+pub struct Exporter{
+    dry_run: bool,
+    skip_existing: bool,
+    // which media a file was written for, another media on the same path is a collision
+    seen: HashMap<PathBuf, String>,
+    pub report: ExportReport,
+}
+
+// the show folder holds tvshow.nfo, one level above a season folder
+fn show_folder(video_path: &Path) -> Option<PathBuf>{
+    if let Some(path) = nfo::tv_path(video_path){
+        return path.parent().map(|folder| folder.to_path_buf())
+    }
+    let folder = video_path.parent()?;
+    match folder.file_name().and_then(|name| name.to_str()){
+        Some(name) if SEASON_FOLDER.is_match(name) => folder.parent().map(|parent| parent.to_path_buf()),
+        _ => Some(folder.to_path_buf()),
+    }
+}
+
+impl Exporter{
+    pub fn new(dry_run: bool, skip_existing: bool) -> Exporter{
+        Exporter{
+            dry_run,
+            skip_existing,
+            seen: HashMap::new(),
+            report: ExportReport::default(),
+        }
+    }
+
+    // false when the file is already handled, kept, or would only be planned
+    fn claim(&mut self, path: &Path, owner: &str) -> bool{
+        if let Some(previous) = self.seen.get(path){
+            if previous != owner{
+                self.report.skipped.push(format!("{}: already written for {}", path.display(), previous));
+            }
+            return false
+        }
+        self.seen.insert(path.to_path_buf(), owner.to_string());
+        if self.skip_existing && path.exists(){
+            self.report.skipped.push(path.display().to_string());
+            return false
+        }
+        self.report.written.push(path.display().to_string());
+        !self.dry_run
+    }
+
+    fn write_nfo(&mut self, path: PathBuf, nfos: &[Nfo], root: &str, owner: &str){
+        if !self.claim(&path, owner){
+            return
+        }
+        let result = Nfo::to_xml(nfos, root).and_then(|xml| Ok(fs::write(&path, xml)?));
+        if let Err(e) = result{
+            self.report.written.pop();
+            self.report.errors.push(format!("{}: {}", path.display(), e));
+        }
+    }
+
+    fn copy_rsc(&mut self, path: PathBuf, rsc_path: &str, owner: &str){
+        if rsc_path.is_empty(){
+            return
+        }
+        let source = match rsc::best(rsc_path, None){
+            Some(best) => format!("{}/{}", *RSCPATH.lock().unwrap(), best),
+            None => {
+                if !self.seen.contains_key(&path){
+                    self.seen.insert(path.clone(), owner.to_string());
+                    self.report.errors.push(format!("{}: {} is not cached", path.display(), rsc_path));
+                }
+                return
+            }
+        };
+        if !self.claim(&path, owner){
+            return
+        }
+        if let Err(e) = fs::copy(&source, &path){
+            self.report.written.pop();
+            self.report.errors.push(format!("{}: {}", path.display(), e));
+        }
+    }
+
+    pub fn video(&mut self, video: &Video) -> PyResult<()>{
+        let video_path = Path::new(&video.path);
+        let folder = match video_path.parent(){
+            Some(folder) => folder.to_path_buf(),
+            None => return Ok(()),
+        };
+
+        if video.media_type == 0{
+            if let Some(mut movie) = video.movie()?{
+                movie.set_persons()?;
+                let owner = format!("movie {}", movie.id);
+                let (nfo, poster, fanart) = match video_path.file_stem().and_then(|stem| stem.to_str()){
+                    Some(stem) if nfo::shared_folder(video_path)? => (format!("{}.nfo", stem), format!("{}-poster.jpg", stem), format!("{}-fanart.jpg", stem)),
+                    _ => ("movie.nfo".to_string(), "poster.jpg".to_string(), "fanart.jpg".to_string()),
+                };
+                self.write_nfo(folder.join(nfo), &[Nfo::from(&movie)], "movie", &owner);
+                self.copy_rsc(folder.join(poster), &movie.poster_path, &owner);
+                self.copy_rsc(folder.join(fanart), &movie.backdrop_path, &owner);
+            }
+            return Ok(())
+        }
+
+        // a multi episode file gets one episodedetails per linked episode, in link order
+        let mut episodes = video.tv_episodes()?;
+        let mut nfos = Vec::new();
+        for episode in &mut episodes{
+            episode.set_persons()?;
+            nfos.push(Nfo::from(&*episode));
+        }
+        if let Some(mut episode) = episodes.into_iter().next(){
+            let owner = format!("episode {}", episode.id);
+            self.write_nfo(video_path.with_extension("nfo"), &nfos, "episodedetails", &owner);
+            episode.set_tv()?;
+            if let (Some(tv), Some(show)) = (&mut episode.tv, show_folder(video_path)){
+                tv.set_persons()?;
+                let owner = format!("tv {}", tv.id);
+                self.write_nfo(show.join("tvshow.nfo"), &[Nfo::from(&*tv)], "tvshow", &owner);
+                self.copy_rsc(show.join("poster.jpg"), &tv.poster_path, &owner);
+                self.copy_rsc(show.join("fanart.jpg"), &tv.backdrop_path, &owner);
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn export(user: &String, dry_run: bool, skip_existing: bool) -> PyResult<ExportReport>{
+    let mut exporter = Exporter::new(dry_run, skip_existing);
+    for result in VideoSearch::new(user).results(None, None)?{
+        let exported = result.full().and_then(|video| exporter.video(&video));
+        if let Err(e) = exported{
+            exporter.report.errors.push(format!("video {}: {}", result.id, e));
+        }
+    }
+    Ok(exporter.report)
+}
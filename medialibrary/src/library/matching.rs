@@ -0,0 +1,220 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use pyo3::prelude::*;
+
+use crate::rustmdb::discover::MediaKind;
+use crate::rustmdb::model::{SearchMovie, SearchTv};
+use crate::rustmdb::title::{normalize, title_score};
+
+use super::provider::{self, MetadataProvider};
+
+lazy_static! {
+    pub static ref MATCH_THRESHOLD: Arc<Mutex<f64>> = Arc::new(Mutex::new(0.8));
+}
+
+const TITLE_WEIGHT: f64 = 0.6;
+const YEAR_WEIGHT: f64 = 0.25;
+const LANGUAGE_WEIGHT: f64 = 0.05;
+const POPULARITY_WEIGHT: f64 = 0.1;
+
+// only the best ranked candidates pay for an alternative titles request
+const ALTERNATIVE_LOOKUPS: usize = 3;
+
+#[pyclass]
+#[derive(Debug, Clone, Serialize)]
+pub struct Candidate{
+    #[pyo3(get)]
+    pub media_type: String,
+    #[pyo3(get)]
+    pub id: u64,
+    #[pyo3(get)]
+    pub title: String,
+    #[pyo3(get)]
+    pub original_title: String,
+    #[pyo3(get)]
+    pub original_language: String,
+    #[pyo3(get)]
+    pub year: Option<u64>,
+    #[pyo3(get)]
+    pub popularity: f64,
+    #[pyo3(get)]
+    pub confidence: f64,
+    #[pyo3(get)]
+    pub explanation: Vec<String>,
+    #[serde(skip)]
+    titles: Vec<String>,
+}
+
+#[pymethods]
+impl Candidate{
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+#[pyclass]
+#[derive(Debug, Clone, Serialize)]
+pub struct Match{
+    #[pyo3(get)]
+    pub query: String,
+    #[pyo3(get)]
+    pub year: Option<u64>,
+    #[pyo3(get)]
+    pub threshold: f64,
+    #[pyo3(get)]
+    pub accepted: Option<u64>,
+    #[pyo3(get)]
+    pub candidates: Vec<Candidate>,
+}
+
+#[pymethods]
+impl Match{
+    pub fn best(&self) -> Option<Candidate>{
+        self.candidates.first().cloned()
+    }
+
+    pub fn json(&self) -> PyResult<String>{
+        Ok(serde_json::to_string(self).unwrap())
+    }
+
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+impl From<SearchMovie> for Candidate{
+    fn from(movie: SearchMovie) -> Candidate{
+        Candidate{
+            media_type: MediaKind::Movie.name().to_string(),
+            id: movie.id,
+            year: year_of(&movie.release_date),
+            titles: vec![movie.title.clone(), movie.original_title.clone()],
+            title: movie.title,
+            original_title: movie.original_title,
+            original_language: movie.original_language,
+            popularity: movie.popularity,
+            confidence: 0.0,
+            explanation: Vec::new(),
+        }
+    }
+}
+
+impl From<SearchTv> for Candidate{
+    fn from(tv: SearchTv) -> Candidate{
+        Candidate{
+            media_type: MediaKind::Tv.name().to_string(),
+            id: tv.id,
+            year: year_of(&tv.first_air_date),
+            titles: vec![tv.name.clone(), tv.original_name.clone()],
+            title: tv.name,
+            original_title: tv.original_name,
+            original_language: tv.original_language,
+            popularity: tv.popularity,
+            confidence: 0.0,
+            explanation: Vec::new(),
+        }
+    }
+}
+
+fn year_of(date: &Option<String>) -> Option<u64>{
+    date.as_ref().and_then(|date| date.get(..4)).and_then(|year| year.parse().ok())
+}
+
+fn year_score(year: u64, candidate: Option<u64>) -> f64{
+    match candidate{
+        Some(candidate) if candidate == year => 1.0,
+        Some(candidate) if candidate + 1 == year || candidate == year + 1 => 0.75,
+        Some(_) => 0.0,
+        None => 0.3,
+    }
+}
+
+fn score(candidate: &mut Candidate, query: &str, year: Option<u64>, original_language: Option<&str>, max_popularity: f64){
+    let mut parts = Vec::new();
+
+    let (title, matched) = title_score(query, &candidate.titles);
+    parts.push((TITLE_WEIGHT, title, format!("title {:.2} with \"{}\"", title, matched)));
+
+    if let Some(year) = year{
+        let value = year_score(year, candidate.year);
+        let found = candidate.year.map(|year| year.to_string()).unwrap_or_else(|| "unknown".to_string());
+        parts.push((YEAR_WEIGHT, value, format!("year {:.2}, {} for {}", value, found, year)));
+    }
+
+    if let Some(language) = original_language{
+        let value = if candidate.original_language == language { 1.0 } else { 0.0 };
+        parts.push((LANGUAGE_WEIGHT, value, format!("language {:.2}, {} for {}", value, candidate.original_language, language)));
+    }
+
+    if max_popularity > 0.0{
+        let value = (1.0 + candidate.popularity).ln() / (1.0 + max_popularity).ln();
+        parts.push((POPULARITY_WEIGHT, value, format!("popularity {:.2}", value)));
+    }
+
+    let weights: f64 = parts.iter().map(|(weight, _, _)| weight).sum();
+    candidate.confidence = parts.iter().map(|(weight, value, _)| weight * value).sum::<f64>() / weights;
+    candidate.explanation = parts.into_iter().map(|(_, _, text)| text).collect();
+}
+
+fn rank(provider: &dyn MetadataProvider, kind: MediaKind, query: &str, year: Option<u64>, original_language: Option<&str>,
+        limit: usize, mut candidates: Vec<Candidate>) -> Match{
+    let mut seen = HashSet::new();
+    candidates.retain(|candidate| seen.insert(candidate.id));
+
+    let normalized = normalize(query);
+    let max_popularity = candidates.iter().map(|candidate| candidate.popularity).fold(0.0, f64::max);
+    for candidate in candidates.iter_mut(){
+        score(candidate, &normalized, year, original_language, max_popularity);
+    }
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    for candidate in candidates.iter_mut().take(ALTERNATIVE_LOOKUPS){
+        if title_score(&normalized, &candidate.titles).0 >= 1.0{
+            continue
+        }
+        // a failed lookup only costs the bonus, the match is still usable
+        if let Ok(titles) = provider.alternative_titles(kind, candidate.id){
+            candidate.titles.extend(titles);
+            score(candidate, &normalized, year, original_language, max_popularity);
+        }
+    }
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    candidates.truncate(limit.max(1));
+
+    let threshold = *MATCH_THRESHOLD.lock().unwrap();
+    Match{
+        query: query.to_string(),
+        year,
+        threshold,
+        accepted: candidates.first().filter(|candidate| candidate.confidence >= threshold).map(|candidate| candidate.id),
+        candidates,
+    }
+}
+
+// the year only narrows a second search, the broad one keeps the neighbouring years
+pub fn match_movie(title: &str, year: Option<u64>, original_language: Option<&str>, limit: usize, provider: Option<&str>) -> PyResult<Match>{
+    let provider = provider::get(provider)?;
+    let mut candidates: Vec<Candidate> = provider.search_movie(title, None, None)?.into_iter().map(Candidate::from).collect();
+    if let Some(year) = year{
+        candidates.extend(provider.search_movie(title, Some(year), None)?.into_iter().map(Candidate::from));
+    }
+    Ok(rank(&*provider, MediaKind::Movie, title, year, original_language, limit, candidates))
+}
+
+pub fn match_tv(title: &str, year: Option<u64>, original_language: Option<&str>, limit: usize, provider: Option<&str>) -> PyResult<Match>{
+    let provider = provider::get(provider)?;
+    let mut candidates: Vec<Candidate> = provider.search_tv(title, None, None)?.into_iter().map(Candidate::from).collect();
+    if let Some(year) = year{
+        candidates.extend(provider.search_tv(title, Some(year), None)?.into_iter().map(Candidate::from));
+    }
+    Ok(rank(&*provider, MediaKind::Tv, title, year, original_language, limit, candidates))
+}
@@ -7,11 +7,21 @@ use pyo3::prelude::*;
 
 
 mod update_db;
+mod rsc;
+mod refresh;
+mod pool;
+mod provider;
+mod nfo;
+mod export;
+pub mod matching;
+pub mod numbering;
+pub mod release;
 pub mod video;
 pub mod movie;
 pub mod tv;
 pub mod cast;
 pub mod keyword;
+pub mod production;
 pub mod trailer;
 pub mod collection;
 pub mod genre;
@@ -22,10 +32,17 @@ use self::cast::{Person, PersonSearch};
 use self::collection::{Collection, CollectionSearch};
 use self::movie::{Movie, MovieSearch};
 use self::tv::{Tv, Season, Episode, TvSearch, EpisodeSearch};
+use self::refresh::RefreshReport;
+use self::export::ExportReport;
+use self::matching::Match;
+use self::release::ReleaseName;
+use self::numbering::EpisodeMapping;
 use self::video::VideoSearch;
 
 lazy_static! {
     pub static ref RSCPATH: Arc<Mutex<String>> = Arc::new(Mutex::new("".to_string()));
+    pub static ref LANGUAGES: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    pub static ref WORKERS: Arc<Mutex<usize>> = Arc::new(Mutex::new(8));
 }
 
 #[pyclass]
@@ -35,12 +52,108 @@ pub struct Library{
 #[pymethods]
 impl Library {
     #[new]
-    pub fn new(database_path: &str, rsc_path: String) -> Self {
+    #[args(languages = "None")]
+    pub fn new(database_path: &str, rsc_path: String, languages: Option<Vec<String>>) -> Self {
         *RSCPATH.lock().unwrap() = rsc_path.to_string();
+        if let Some(languages) = languages{
+            *LANGUAGES.lock().unwrap() = languages;
+        }
         DATABASE.connect(database_path);
         Library{ }
     }
 
+    pub fn set_languages(&self, languages: Vec<String>){
+        *LANGUAGES.lock().unwrap() = languages;
+    }
+
+    pub fn languages(&self) -> Vec<String>{
+        LANGUAGES.lock().unwrap().clone()
+    }
+
+    pub fn set_user_language(&self, user: String, language: String) -> PyResult<()>{
+        Ok(DATABASE.set_user_language(&user, &language)?)
+    }
+
+    pub fn user_language(&self, user: String) -> PyResult<Option<String>>{
+        Ok(DATABASE.get_user_language(&user)?)
+    }
+
+    pub fn set_workers(&self, workers: usize){
+        *WORKERS.lock().unwrap() = workers.max(1);
+    }
+
+    pub fn set_provider(&self, name: &str) -> PyResult<()>{
+        provider::set_default(name)
+    }
+
+    pub fn provider(&self) -> String{
+        provider::PROVIDER.lock().unwrap().clone()
+    }
+
+    pub fn providers(&self) -> Vec<String>{
+        provider::names()
+    }
+
+    #[args(year = "None", language = "None", provider = "None")]
+    pub fn search_movie_json(&self, query: &str, year: Option<u64>, language: Option<&str>, provider: Option<&str>) -> PyResult<String>{
+        let movies = provider::get(provider)?.search_movie(query, year, language)?;
+        Ok(serde_json::to_string(&movies).unwrap())
+    }
+
+    #[args(year = "None", language = "None", provider = "None")]
+    pub fn search_tv_json(&self, query: &str, year: Option<u64>, language: Option<&str>, provider: Option<&str>) -> PyResult<String>{
+        let tvs = provider::get(provider)?.search_tv(query, year, language)?;
+        Ok(serde_json::to_string(&tvs).unwrap())
+    }
+
+    #[args(year = "None", original_language = "None", limit = "5", provider = "None")]
+    pub fn match_movie(&self, title: &str, year: Option<u64>, original_language: Option<&str>, limit: usize, provider: Option<&str>) -> PyResult<Match>{
+        matching::match_movie(title, year, original_language, limit, provider)
+    }
+
+    #[args(year = "None", original_language = "None", limit = "5", provider = "None")]
+    pub fn match_tv(&self, title: &str, year: Option<u64>, original_language: Option<&str>, limit: usize, provider: Option<&str>) -> PyResult<Match>{
+        matching::match_tv(title, year, original_language, limit, provider)
+    }
+
+    pub fn parse_release(&self, path: &str) -> ReleaseName{
+        release::parse(path)
+    }
+
+    #[args(provider = "None")]
+    pub fn resolve_absolute(&self, tv_id: u64, absolute: u64, provider: Option<&str>) -> PyResult<EpisodeMapping>{
+        numbering::resolve_absolute(provider::get(provider)?.as_ref(), tv_id, absolute)
+    }
+
+    #[args(provider = "None")]
+    pub fn resolve_date(&self, tv_id: u64, date: &str, provider: Option<&str>) -> PyResult<EpisodeMapping>{
+        match release::parse_date(date){
+            Some(date) => numbering::resolve_date(provider::get(provider)?.as_ref(), tv_id, &date),
+            None => Err(Error::new(ErrorKind::ParseName, format!("could not parse date {}", date), "resolve_date").into()),
+        }
+    }
+
+    pub fn set_match_threshold(&self, threshold: f64){
+        *matching::MATCH_THRESHOLD.lock().unwrap() = threshold.clamp(0.0, 1.0);
+    }
+
+    pub fn match_threshold(&self) -> f64{
+        *matching::MATCH_THRESHOLD.lock().unwrap()
+    }
+
+    pub fn set_rsc_sizes(&self, sizes: Vec<u32>){
+        rsc::set_sizes(sizes);
+    }
+
+    pub fn rsc_sizes(&self) -> Vec<u32>{
+        rsc::RSC_SIZES.lock().unwrap().clone()
+    }
+
+    #[args(width = "None")]
+    pub fn rsc(&self, path: &str, width: Option<u32>) -> Option<String>{
+        rsc::best(path, width)
+    }
+
     pub fn new_video(&self, user: String,  path: String, media_type: u8) -> PyResult<Video> {
         Ok(self.video(user.clone(), DATABASE.create_video(Video::from_path(user, path, media_type)?)?)?.unwrap())
     }
@@ -77,6 +190,25 @@ impl Library {
         Ok(EpisodeSearch::new(&user).tv(tv_id)?.season(season_number)?.episode(episode_number)?.last()?)
     }
 
+    #[args(provider = "None")]
+    pub fn import_season(&self, user: String, tv_id: u64, season_number: u64, provider: Option<&str>) -> PyResult<()>{
+        update_db::import_season(&user, provider::get(provider)?.as_ref(), tv_id, season_number)
+    }
+
+    #[args(max_age = "14", use_changes = "true", provider = "None")]
+    pub fn refresh(&self, user: String, max_age: u64, use_changes: bool, provider: Option<&str>) -> PyResult<RefreshReport>{
+        refresh::refresh(&user, provider::get(provider)?.as_ref(), max_age, use_changes)
+    }
+
+    pub fn update_tv_artwork(&self) -> PyResult<Vec<String>>{
+        update_db::update_missing_rscs(DATABASE.tv_artwork_paths()?)
+    }
+
+    #[args(dry_run = "false", skip_existing = "true")]
+    pub fn export_nfo(&self, user: String, dry_run: bool, skip_existing: bool) -> PyResult<ExportReport>{
+        export::export(&user, dry_run, skip_existing)
+    }
+
     pub fn tv_episodes(&self, user: String) -> EpisodeSearch{
         EpisodeSearch::new(&user)
     }
@@ -118,7 +250,9 @@ impl Library {
 pub enum ErrorKind{
     ParseName,
     NotFound,
-    MediaType
+    MediaType,
+    Provider,
+    Nfo,
 }
 
 #[derive(Debug)]
@@ -10,6 +10,7 @@ use super::cast::Crew;
 use super::collection::CollectionResult;
 use super::collection::CollectionSearch;
 use super::keyword::Keyword;
+use super::production::{self, Company, Country, Language};
 use super::trailer::Trailer;
 use super::video::VideoResult;
 use super::video::VideoSearch;
@@ -45,6 +46,16 @@ pub struct Movie{
     #[pyo3(get)]
     pub status: String,
     #[pyo3(get)]
+    pub imdb_id: String,
+    #[pyo3(get)]
+    pub runtime: u64,
+    #[pyo3(get)]
+    pub budget: u64,
+    #[pyo3(get)]
+    pub revenue: u64,
+    #[pyo3(get)]
+    pub homepage: String,
+    #[pyo3(get)]
     pub genres: Vec<String>,
     #[pyo3(get)]
     pub adding: String,
@@ -63,6 +74,12 @@ pub struct Movie{
     #[pyo3(get)]
     pub collection: Vec<CollectionResult>,
     #[pyo3(get)]
+    pub companies: Vec<Company>,
+    #[pyo3(get)]
+    pub countries: Vec<Country>,
+    #[pyo3(get)]
+    pub languages: Vec<Language>,
+    #[pyo3(get)]
     pub updated: String,
 }
 
@@ -95,6 +112,13 @@ impl Movie{
         Ok(())
     }
 
+    pub fn set_production(&mut self) -> PyResult<()>{
+        self.companies = DATABASE.get_companies("Movie", self.id)?;
+        self.countries = DATABASE.get_countries("Movie", self.id)?;
+        self.languages = DATABASE.get_languages("Movie", self.id)?;
+        Ok(())
+    }
+
     pub fn set_watched(&self, b: bool) -> PyResult<()>{
         if b{
             Ok(DATABASE.set_movie_watched(self.user.clone(), self.id, self.watched+1)?)
@@ -156,6 +180,8 @@ pub struct MovieResult{
     pub adding: String,
     #[pyo3(get)]
     pub watched: u64,
+    #[pyo3(get)]
+    pub runtime: u64,
 }
 
 #[pymethods]
@@ -205,10 +231,43 @@ impl MovieSearch{
         self.find("MovieCollectionLinks.collection_id", "=", Some(collection_id.to_string()))
     }
 
+    pub fn imdb_id(&mut self, imdb_id: String) -> PyResult<MovieSearch>{
+        self.find("Movies.imdb_id", "=", Some(imdb_id))
+    }
+
     pub fn crew(&mut self, person_id: u64) -> PyResult<MovieSearch>{
         self.find("MovieCrews.person_id", "=", Some(person_id.to_string()))
     }
 
+    // unknown runtimes are stored as 0 and never count as short
+    pub fn max_runtime(&mut self, minutes: u64) -> PyResult<MovieSearch>{
+        self.find("CAST(NULLIF(Movies.runtime, 0) AS INTEGER)", "<=", Some(minutes.to_string()))
+    }
+
+    pub fn min_runtime(&mut self, minutes: u64) -> PyResult<MovieSearch>{
+        self.find("Movies.runtime", ">=", Some(minutes.to_string()))
+    }
+
+    pub fn min_budget(&mut self, budget: u64) -> PyResult<MovieSearch>{
+        self.find("Movies.budget", ">=", Some(budget.to_string()))
+    }
+
+    pub fn min_revenue(&mut self, revenue: u64) -> PyResult<MovieSearch>{
+        self.find("Movies.revenue", ">=", Some(revenue.to_string()))
+    }
+
+    pub fn company(&mut self, company_id: u64) -> PyResult<MovieSearch>{
+        self.find(&production::linked("MovieCompanyLinks", "company_id", "movie_id", "Movies.id"), "LIKE", production::contains(&company_id.to_string()))
+    }
+
+    pub fn country(&mut self, iso_3166_1: &str) -> PyResult<MovieSearch>{
+        self.find(&production::linked("MovieCountryLinks", "country", "movie_id", "Movies.id"), "LIKE", production::contains(iso_3166_1))
+    }
+
+    pub fn spoken_language(&mut self, iso_639_1: &str) -> PyResult<MovieSearch>{
+        self.find(&production::linked("MovieLanguageLinks", "language", "movie_id", "Movies.id"), "LIKE", production::contains(iso_639_1))
+    }
+
     pub fn find(&mut self, column: &str, operator: &str, value: Option<String>) -> PyResult<MovieSearch>{
         if let Some(value) = value {
             self.parameters.insert(column.to_string(), Some((operator.to_string(), value)));
@@ -0,0 +1,623 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+use serde::Serialize;
+
+use crate::database::DATABASE;
+use crate::rustmdb::cache::stable_hash;
+use crate::rustmdb::model::{self, Cast, Credits, Crew, ExternalIds, Genre, KeywordsMovie, KeywordsTv, Translations, Videos};
+
+use super::genre;
+use super::movie::Movie;
+use super::tv::{Episode, Tv};
+use super::cast;
+use super::{Error, ErrorKind};
+
+// ids for entries without a tmdb id, clear of tmdb ids and below the 2^53 javascript limit
+pub const LOCAL_ID_BASE: u64 = 1 << 50;
+
+pub fn local_id(kind: &str, key: &str) -> u64{
+    LOCAL_ID_BASE + stable_hash(&format!("{}\0{}", kind, key)) % LOCAL_ID_BASE
+}
+
+#[derive(Debug, Deserialize, Serialize, Default)]
+struct UniqueId{
+    #[serde(rename = "@type", default)]
+    kind: String,
+    #[serde(rename = "@default", skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+    #[serde(rename = "$text", default)]
+    value: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default)]
+struct Rating{
+    #[serde(rename = "@name", skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(rename = "@max", skip_serializing_if = "Option::is_none")]
+    max: Option<String>,
+    #[serde(rename = "@default", skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+    value: Option<String>,
+    votes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default)]
+struct Ratings{
+    #[serde(default)]
+    rating: Vec<Rating>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default)]
+struct Actor{
+    #[serde(default)]
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    order: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tmdbid: Option<String>,
+}
+
+// movie.nfo, tvshow.nfo and episodedetails share their fields
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct Nfo{
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    originaltitle: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    plot: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outline: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tagline: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    year: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    premiered: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aired: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    runtime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    season: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    episode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rating: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    votes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ratings: Option<Ratings>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    uniqueid: Vec<UniqueId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tmdbid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    imdbid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tvdbid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    genre: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    director: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    credits: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    actor: Vec<Actor>,
+}
+
+fn text(value: &Option<String>) -> Option<String>{
+    value.as_ref().map(|value| value.trim().to_string()).filter(|value| !value.is_empty())
+}
+
+fn number<T: std::str::FromStr>(value: &Option<String>) -> Option<T>{
+    text(value).and_then(|value| value.parse().ok())
+}
+
+impl Nfo{
+    pub fn read(path: &Path) -> PyResult<Nfo>{
+        let content = fs::read_to_string(path)?;
+        match quick_xml::de::from_str(&content){
+            Ok(nfo) => Ok(nfo),
+            Err(e) => Err(Error::new(ErrorKind::Nfo, e.to_string(), &format!("nfo {}", path.display())).into()),
+        }
+    }
+
+    fn unique(&self, kind: &str) -> Option<String>{
+        let found = self.uniqueid.iter()
+            .find(|id| id.kind.eq_ignore_ascii_case(kind))
+            .and_then(|id| text(&Some(id.value.clone())));
+        if found.is_some(){
+            return found
+        }
+        match kind{
+            "tmdb" => text(&self.tmdbid),
+            "imdb" => text(&self.imdbid).or_else(|| text(&self.id).filter(|id| id.starts_with("tt"))),
+            "tvdb" => text(&self.tvdbid),
+            _ => None,
+        }
+    }
+
+    pub fn tmdb_id(&self) -> Option<u64>{
+        self.unique("tmdb").and_then(|id| id.parse().ok())
+    }
+
+    fn title(&self) -> String{
+        text(&self.title).unwrap_or_default()
+    }
+
+    fn original_title(&self) -> String{
+        text(&self.originaltitle).unwrap_or_else(|| self.title())
+    }
+
+    fn date(&self) -> String{
+        text(&self.premiered).or_else(|| text(&self.aired)).or_else(|| text(&self.year)).unwrap_or_default()
+    }
+
+    // the default rating, else the first one, else the legacy rating and votes tags
+    fn vote(&self) -> (f64, u64){
+        if let Some(ratings) = &self.ratings{
+            let rating = ratings.rating.iter().find(|rating| rating.default.as_deref() == Some("true"))
+                .or_else(|| ratings.rating.first());
+            if let Some(rating) = rating{
+                return (number(&rating.value).unwrap_or(0.0), number(&rating.votes).unwrap_or(0))
+            }
+        }
+        (number(&self.rating).unwrap_or(0.0), number(&self.votes).unwrap_or(0))
+    }
+
+    fn genres(&self, known: Vec<genre::Genre>) -> Vec<Genre>{
+        let known: HashMap<String, u64> = known.into_iter().map(|genre| (genre.name.to_lowercase(), genre.id)).collect();
+        self.genre.iter().filter_map(|name| text(&Some(name.clone()))).map(|name| Genre{
+            id: known.get(&name.to_lowercase()).copied().unwrap_or_else(|| local_id("genre", &name)),
+            name,
+        }).collect()
+    }
+
+    fn credits(&self, media_key: &str) -> Credits{
+        let cast = self.actor.iter().enumerate().filter(|(_, actor)| !actor.name.trim().is_empty()).map(|(index, actor)| {
+            let name = actor.name.trim().to_string();
+            let id = number(&actor.tmdbid).unwrap_or_else(|| local_id("person", &name));
+            Cast{
+                adult: false,
+                gender: None,
+                id,
+                known_for_department: Some("Acting".to_string()),
+                original_name: name.clone(),
+                name,
+                popularity: 0.0,
+                profile_path: None,
+                cast_id: None,
+                character: text(&actor.role),
+                credit_id: format!("nfo-{}-{}", media_key, id),
+                order: number(&actor.order).unwrap_or(index as u64),
+            }
+        }).collect();
+
+        let jobs = self.director.iter().map(|name| (name, "Director", "Directing"))
+            .chain(self.credits.iter().map(|name| (name, "Screenplay", "Writing")));
+        let crew = jobs.filter_map(|(name, job, department)| text(&Some(name.clone())).map(|name| (name, job, department))).map(|(name, job, department)| {
+            let id = local_id("person", &name);
+            Crew{
+                adult: false,
+                gender: None,
+                id,
+                known_for_department: Some(department.to_string()),
+                original_name: name.clone(),
+                name,
+                popularity: 0.0,
+                profile_path: None,
+                credit_id: format!("nfo-{}-{}-{}", media_key, job, id),
+                department: department.to_string(),
+                job: job.to_string(),
+            }
+        }).collect();
+
+        Credits{ cast, crew }
+    }
+
+    fn movie(&self) -> PyResult<model::Movie>{
+        let title = self.title();
+        let id = self.tmdb_id().unwrap_or_else(|| local_id("movie", &format!("{} {}", title, self.date())));
+        let (vote_average, vote_count) = self.vote();
+        Ok(model::Movie{
+            id,
+            budget: 0,
+            revenue: 0,
+            runtime: number(&self.runtime),
+            poster_path: None,
+            backdrop_path: None,
+            homepage: None,
+            original_title: self.original_title(),
+            title,
+            original_language: "".to_string(),
+            overview: text(&self.plot).or_else(|| text(&self.outline)),
+            release_date: self.date(),
+            popularity: 0.0,
+            adult: false,
+            vote_count,
+            vote_average,
+            tagline: text(&self.tagline),
+            status: text(&self.status).unwrap_or_default(),
+            genres: self.genres(DATABASE.genre_movie()?),
+            production_companies: Vec::new(),
+            production_countries: Vec::new(),
+            spoken_languages: Vec::new(),
+            credits: self.credits(&format!("movie-{}", id)),
+            videos: Videos{ results: Vec::new() },
+            keywords: KeywordsMovie{ keywords: Vec::new() },
+            imdb_id: self.unique("imdb"),
+            belongs_to_collection: None,
+            translations: Translations::default(),
+        })
+    }
+
+    fn tv(&self) -> PyResult<model::Tv>{
+        let title = self.title();
+        let id = self.tmdb_id().unwrap_or_else(|| local_id("tv", &title));
+        let (vote_average, vote_count) = self.vote();
+        Ok(model::Tv{
+            id,
+            backdrop_path: None,
+            poster_path: None,
+            episode_run_time: Vec::new(),
+            first_air_date: Some(self.date()),
+            genres: self.genres(DATABASE.genre_tv()?),
+            homepage: None,
+            in_production: false,
+            languages: Vec::new(),
+            last_air_date: None,
+            original_name: self.original_title(),
+            name: title,
+            number_of_episodes: 0,
+            number_of_seasons: 0,
+            origin_country: Vec::new(),
+            original_language: "".to_string(),
+            overview: text(&self.plot),
+            popularity: 0.0,
+            production_companies: Vec::new(),
+            production_countries: Vec::new(),
+            spoken_languages: Vec::new(),
+            credits: self.credits(&format!("tv-{}", id)),
+            status: text(&self.status).unwrap_or_default(),
+            tagline: text(&self.tagline).unwrap_or_default(),
+            vote_count,
+            vote_average,
+            created_by: Vec::new(),
+            last_episode_to_air: None,
+            networks: Vec::new(),
+            seasons: Vec::new(),
+            videos: Videos{ results: Vec::new() },
+            keywords: KeywordsTv{ results: Vec::new() },
+            external_ids: ExternalIds{
+                imdb_id: self.unique("imdb"),
+                tvdb_id: self.unique("tvdb").and_then(|id| id.parse().ok()),
+            },
+            translations: Translations::default(),
+        })
+    }
+
+    fn episode(&self, tv_id: u64, season_number: u64, episode_number: u64) -> model::TvEpisode{
+        let id = self.tmdb_id().unwrap_or_else(|| local_id("episode", &format!("{} s{}e{}", tv_id, season_number, episode_number)));
+        let (vote_average, vote_count) = self.vote();
+        model::TvEpisode{
+            air_date: text(&self.aired).or_else(|| text(&self.premiered)).unwrap_or_default(),
+            guest_stars: Vec::new(),
+            name: text(&self.title).unwrap_or_else(|| format!("Episode {}", episode_number)),
+            overview: text(&self.plot),
+            id,
+            production_code: None,
+            season_number,
+            episode_number,
+            still_path: None,
+            vote_average,
+            vote_count,
+            credits: self.credits(&format!("episode-{}", id)),
+            external_ids: ExternalIds{
+                imdb_id: self.unique("imdb"),
+                tvdb_id: self.unique("tvdb").and_then(|id| id.parse().ok()),
+            },
+        }
+    }
+}
+
+fn some(value: &str) -> Option<String>{
+    text(&Some(value.to_string()))
+}
+
+fn year(date: &str) -> Option<String>{
+    date.get(..4).and_then(some)
+}
+
+fn tmdb(id: u64) -> Option<String>{
+    if id < LOCAL_ID_BASE{
+        Some(id.to_string())
+    }
+    else{
+        None
+    }
+}
+
+fn unique_ids(tmdb_id: u64, imdb_id: &str, tvdb_id: &str) -> Vec<UniqueId>{
+    let mut ids = Vec::new();
+    if let Some(value) = tmdb(tmdb_id){
+        ids.push(UniqueId{ kind: "tmdb".to_string(), default: Some("true".to_string()), value });
+    }
+    if let Some(value) = some(imdb_id){
+        ids.push(UniqueId{ kind: "imdb".to_string(), default: None, value });
+    }
+    if let Some(value) = some(tvdb_id){
+        ids.push(UniqueId{ kind: "tvdb".to_string(), default: None, value });
+    }
+    ids
+}
+
+fn ratings(vote_average: f64, vote_count: u64) -> Option<Ratings>{
+    if vote_count == 0{
+        return None
+    }
+    Some(Ratings{ rating: vec![Rating{
+        name: Some("themoviedb".to_string()),
+        max: Some("10".to_string()),
+        default: Some("true".to_string()),
+        value: Some(vote_average.to_string()),
+        votes: Some(vote_count.to_string()),
+    }]})
+}
+
+fn actors(cast: &[cast::Cast]) -> Vec<Actor>{
+    let mut cast: Vec<&cast::Cast> = cast.iter().collect();
+    cast.sort_by_key(|cast| cast.ord);
+    cast.into_iter().map(|cast| Actor{
+        name: cast.name.clone(),
+        role: some(&cast.character),
+        order: Some(cast.ord.to_string()),
+        tmdbid: tmdb(cast.id),
+    }).collect()
+}
+
+fn jobs(crew: &[cast::Crew], jobs: &[&str]) -> Vec<String>{
+    let mut names: Vec<String> = crew.iter().filter(|crew| jobs.contains(&crew.job.as_str())).map(|crew| crew.name.clone()).collect();
+    names.dedup();
+    names
+}
+
+// exports expect the persons to be loaded
+impl From<&Movie> for Nfo{
+    fn from(movie: &Movie) -> Nfo{
+        Nfo{
+            title: some(&movie.title),
+            originaltitle: some(&movie.original_title),
+            plot: some(&movie.overview),
+            tagline: some(&movie.tagline),
+            year: year(&movie.release_date),
+            premiered: some(&movie.release_date),
+            status: some(&movie.status),
+            runtime: (movie.runtime > 0).then(|| movie.runtime.to_string()),
+            ratings: ratings(movie.vote_average, movie.vote_count.max(0) as u64),
+            uniqueid: unique_ids(movie.id, &movie.imdb_id, ""),
+            genre: movie.genres.clone(),
+            director: jobs(&movie.crew, &["Director"]),
+            credits: jobs(&movie.crew, &["Screenplay", "Writer"]),
+            actor: actors(&movie.cast),
+            ..Nfo::default()
+        }
+    }
+}
+
+impl From<&Tv> for Nfo{
+    fn from(tv: &Tv) -> Nfo{
+        Nfo{
+            title: some(&tv.title),
+            originaltitle: some(&tv.original_title),
+            plot: some(&tv.overview),
+            year: year(&tv.release_date),
+            premiered: some(&tv.release_date),
+            status: some(&tv.status),
+            ratings: ratings(tv.vote_average, tv.vote_count.max(0) as u64),
+            uniqueid: unique_ids(tv.id, &tv.imdb_id, &tv.tvdb_id),
+            genre: tv.genres.clone(),
+            actor: actors(&tv.cast),
+            ..Nfo::default()
+        }
+    }
+}
+
+impl From<&Episode> for Nfo{
+    fn from(episode: &Episode) -> Nfo{
+        Nfo{
+            title: some(&episode.title),
+            plot: some(&episode.overview),
+            season: Some(episode.season_number.to_string()),
+            episode: Some(episode.episode_number.to_string()),
+            aired: some(&episode.release_date),
+            ratings: ratings(episode.vote_average, episode.vote_count),
+            uniqueid: unique_ids(episode.id, &episode.imdb_id, &episode.tvdb_id),
+            director: jobs(&episode.crew, &["Director"]),
+            credits: jobs(&episode.crew, &["Screenplay", "Writer"]),
+            actor: actors(&episode.cast),
+            ..Nfo::default()
+        }
+    }
+}
+
+impl Nfo{
+    // a file for several episodes repeats the root element, one after the other
+    pub fn to_xml(nfos: &[Nfo], root: &str) -> PyResult<String>{
+        let mut xml = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\" ?>\n".to_string();
+        for nfo in nfos{
+            xml += &nfo.element(root)?;
+        }
+        Ok(xml)
+    }
+
+    fn element(&self, root: &str) -> PyResult<String>{
+        let mut xml = String::new();
+        let mut serializer = quick_xml::se::Serializer::with_root(&mut xml, Some(root))
+            .map_err(|e| Error::new(ErrorKind::Nfo, e.to_string(), root))?;
+        serializer.indent(' ', 2);
+        if let Err(e) = self.serialize(serializer){
+            return Err(Error::new(ErrorKind::Nfo, e.to_string(), root).into())
+        }
+        xml.push('\n');
+        Ok(xml)
+    }
+}
+
+fn sibling(video_path: &Path, name: &str) -> Option<PathBuf>{
+    let path = video_path.parent()?.join(name);
+    if path.is_file(){
+        Some(path)
+    }
+    else{
+        None
+    }
+}
+
+fn stem_nfo(video_path: &Path) -> Option<PathBuf>{
+    let path = video_path.with_extension("nfo");
+    if path.is_file(){
+        Some(path)
+    }
+    else{
+        None
+    }
+}
+
+// movie.nfo, poster.jpg and fanart.jpg only belong to a folder holding a single video
+pub fn shared_folder(video_path: &Path) -> PyResult<bool>{
+    let folder = match video_path.parent(){
+        Some(folder) => folder.display().to_string(),
+        None => return Ok(false),
+    };
+    Ok(DATABASE.count_folder_videos(&folder)? > 1)
+}
+
+pub fn movie_path(video_path: &Path) -> PyResult<Option<PathBuf>>{
+    if let Some(path) = stem_nfo(video_path){
+        return Ok(Some(path))
+    }
+    if shared_folder(video_path)?{
+        return Ok(None)
+    }
+    Ok(sibling(video_path, "movie.nfo"))
+}
+
+// tvshow.nfo sits next to the episode, or in the show folder above the season folder
+pub fn tv_path(video_path: &Path) -> Option<PathBuf>{
+    video_path.ancestors().skip(1).take(3)
+        .map(|folder| folder.join("tvshow.nfo"))
+        .find(|path| path.is_file())
+}
+
+pub fn episode_path(video_path: &Path) -> Option<PathBuf>{
+    stem_nfo(video_path)
+}
+
+// minimal rows for people the database does not know yet, offline imports have nothing better
+fn create_persons(credits: &Credits) -> PyResult<()>{
+    let mut names: Vec<(u64, String)> = credits.cast.iter().map(|cast| (cast.id, cast.name.clone()))
+        .chain(credits.crew.iter().map(|crew| (crew.id, crew.name.clone())))
+        .collect();
+    names.sort_unstable();
+    names.dedup_by_key(|(id, _)| *id);
+    let ids: Vec<u64> = names.iter().map(|(id, _)| *id).collect();
+    let existing = DATABASE.existing_persons(&ids)?;
+    let mut created = Vec::new();
+    for (id, name) in names{
+        if existing.contains(&id){
+            continue
+        }
+        created.push(id);
+        DATABASE.create_person(&model::Person{
+            birthday: None,
+            known_for_department: None,
+            deathday: None,
+            id,
+            name,
+            also_known_as: Vec::new(),
+            gender: 0,
+            biography: "".to_string(),
+            popularity: 0.0,
+            place_of_birth: None,
+            profile_path: None,
+            adult: false,
+            imdb_id: None,
+            homepage: None,
+            combined_credits: model::CombinedCredits::default(),
+        })?;
+    }
+    DATABASE.mark_skeletons("Persons", &created)?;
+    Ok(())
+}
+
+pub fn import_movie(video_path: &str) -> PyResult<u64>{
+    let video_path = Path::new(video_path);
+    let path = match movie_path(video_path)?{
+        Some(path) => path,
+        None => return Err(Error::new(ErrorKind::NotFound, "no movie nfo".to_string(), &format!("nfo {}", video_path.display())).into()),
+    };
+    let movie = Nfo::read(&path)?.movie()?;
+    if !DATABASE.existing_movies(&[movie.id])?.contains(&movie.id){
+        DATABASE.create_movie(&movie)?;
+        DATABASE.mark_skeletons("Movies", &[movie.id])?;
+        create_persons(&movie.credits)?;
+    }
+    Ok(movie.id)
+}
+
+// numbers come from the episode nfo, else from the file name
+pub fn import_episode(video_path: &str, numbers: Option<(u64, u64)>) -> PyResult<u64>{
+    let video_path = Path::new(video_path);
+    let tv_nfo = match tv_path(video_path){
+        Some(path) => Nfo::read(&path)?,
+        None => return Err(Error::new(ErrorKind::NotFound, "no tvshow nfo".to_string(), &format!("nfo {}", video_path.display())).into()),
+    };
+    let episode_nfo = match episode_path(video_path){
+        Some(path) => Nfo::read(&path)?,
+        None => Nfo::default(),
+    };
+    let (season_number, episode_number) = match (number(&episode_nfo.season), number(&episode_nfo.episode), numbers){
+        (Some(season), Some(episode), _) => (season, episode),
+        (_, _, Some(numbers)) => numbers,
+        _ => return Err(Error::new(ErrorKind::ParseName, "no season and episode number".to_string(), &format!("nfo {}", video_path.display())).into()),
+    };
+
+    let tv = tv_nfo.tv()?;
+    if !DATABASE.existing_tvs(&[tv.id])?.contains(&tv.id){
+        DATABASE.create_tv(&tv)?;
+        DATABASE.mark_skeletons("Tvs", &[tv.id])?;
+        create_persons(&tv.credits)?;
+    }
+
+    if let Some(episode_id) = DATABASE.get_episode_id(tv.id, season_number, episode_number)?{
+        return Ok(episode_id)
+    }
+
+    if DATABASE.get_season_id(tv.id, season_number)?.is_none(){
+        DATABASE.create_season(tv.id, &model::TvSeason{
+            id: local_id("season", &format!("{} s{}", tv.id, season_number)),
+            air_date: None,
+            name: format!("Season {}", season_number),
+            overview: None,
+            poster_path: None,
+            season_number,
+            episodes: Vec::new(),
+            credits: Credits{ cast: Vec::new(), crew: Vec::new() },
+        })?;
+    }
+
+    let episode = episode_nfo.episode(tv.id, season_number, episode_number);
+    DATABASE.create_episode(tv.id, &episode)?;
+    create_persons(&episode.credits)?;
+    Ok(episode.id)
+}
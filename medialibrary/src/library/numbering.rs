@@ -0,0 +1,115 @@
+use pyo3::prelude::*;
+
+use crate::database::DATABASE;
+
+use super::provider::MetadataProvider;
+use super::{Error, ErrorKind};
+
+// where a file name number landed, returned before the video is linked
+#[pyclass]
+#[derive(Debug, Clone, Serialize)]
+pub struct EpisodeMapping{
+    #[pyo3(get)]
+    pub tv_id: u64,
+    #[pyo3(get)]
+    pub absolute: Option<u64>,
+    #[pyo3(get)]
+    pub date: Option<String>,
+    #[pyo3(get)]
+    pub season: u64,
+    #[pyo3(get)]
+    pub episode: u64,
+    #[pyo3(get)]
+    pub source: String,
+}
+
+#[pymethods]
+impl EpisodeMapping{
+    pub fn json(&self) -> PyResult<String>{
+        Ok(serde_json::to_string(self).unwrap())
+    }
+
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+// the provider absolute order first, then the stored season episode counts
+pub fn resolve_absolute(provider: &dyn MetadataProvider, tv_id: u64, absolute: u64) -> PyResult<EpisodeMapping>{
+    let mapping = |season, episode, source: &str| EpisodeMapping{
+        tv_id,
+        absolute: Some(absolute),
+        date: None,
+        season,
+        episode,
+        source: source.to_string(),
+    };
+
+    if absolute > 0{
+        // a failed lookup only loses the group order, the counts still work
+        if let Ok(order) = provider.absolute_order(tv_id){
+            if let Some((season, episode)) = order.get(absolute as usize - 1){
+                return Ok(mapping(*season, *episode, "episode_group"))
+            }
+        }
+
+        // seasons stored from an nfo import have no episode count, one missing season shifts every later number
+        let mut counts = DATABASE.get_season_episode_counts(tv_id)?;
+        if counts.is_empty() || counts.iter().any(|(_, count)| *count == 0){
+            counts = provider.tv(tv_id)?.seasons.iter()
+                .filter(|season| season.season_number > 0)
+                .map(|season| (season.season_number, season.episode_count))
+                .collect();
+        }
+        let mut rest = absolute;
+        for (season, count) in counts{
+            if rest <= count{
+                return Ok(mapping(season, rest, "season_counts"))
+            }
+            rest -= count;
+        }
+    }
+
+    Err(Error::new(ErrorKind::NotFound, format!("no episode {} in absolute order", absolute), &format!("resolve_absolute tv {}", tv_id)).into())
+}
+
+// daily shows, only the last few seasons that started before the date are fetched
+const DATE_LOOKUPS: usize = 3;
+
+pub fn resolve_date(provider: &dyn MetadataProvider, tv_id: u64, date: &str) -> PyResult<EpisodeMapping>{
+    let mapping = |season, episode, source: &str| EpisodeMapping{
+        tv_id,
+        absolute: None,
+        date: Some(date.to_string()),
+        season,
+        episode,
+        source: source.to_string(),
+    };
+
+    if let Some((season, episode)) = DATABASE.get_episode_by_date(tv_id, date)?{
+        return Ok(mapping(season, episode, "episodes"))
+    }
+
+    let mut seasons = DATABASE.get_season_dates(tv_id)?;
+    if seasons.is_empty(){
+        seasons = provider.tv(tv_id)?.seasons.iter()
+            .map(|season| (season.season_number, season.air_date.clone().unwrap_or_default()))
+            .collect();
+    }
+    // the season airing on that date is the last one started before it, specials come last
+    seasons.retain(|(_, start)| !start.is_empty() && start.as_str() <= date);
+    seasons.sort_by(|a, b| (a.0 > 0).cmp(&(b.0 > 0)).then(a.1.cmp(&b.1)).reverse());
+
+    for (season_number, _) in seasons.into_iter().take(DATE_LOOKUPS){
+        let season = provider.season(tv_id, season_number)?;
+        if let Some(episode) = season.episodes.iter().find(|episode| episode.air_date.as_deref() == Some(date)){
+            return Ok(mapping(season_number, episode.episode_number, "seasons"))
+        }
+    }
+
+    Err(Error::new(ErrorKind::NotFound, format!("no episode aired on {}", date), &format!("resolve_date tv {}", tv_id)).into())
+}
@@ -0,0 +1,33 @@
+use std::sync::Mutex;
+use std::thread;
+
+use super::WORKERS;
+
+// runs f over items on at most WORKERS threads, results keep the order of items
+pub fn map<T: Send, R: Send>(items: Vec<T>, f: impl Fn(T) -> R + Sync) -> Vec<R>{
+    let workers = (*WORKERS.lock().unwrap()).min(items.len());
+    if workers <= 1{
+        return items.into_iter().map(f).collect()
+    }
+
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let results = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..workers{
+            scope.spawn(|| loop{
+                let next = queue.lock().unwrap().next();
+                match next{
+                    Some((index, item)) => {
+                        let result = f(item);
+                        results.lock().unwrap().push((index, result));
+                    },
+                    None => break,
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
@@ -0,0 +1,98 @@
+use pyo3::prelude::*;
+
+#[pyclass]
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct Company{
+    #[pyo3(get)]
+    pub id: u64,
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub logo_path: String,
+    #[pyo3(get)]
+    pub origin_country: String,
+}
+
+#[pymethods]
+impl Company {
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+#[pyclass]
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct Network{
+    #[pyo3(get)]
+    pub id: u64,
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub logo_path: String,
+    #[pyo3(get)]
+    pub origin_country: String,
+}
+
+#[pymethods]
+impl Network {
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+#[pyclass]
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct Country{
+    #[pyo3(get)]
+    pub iso_3166_1: String,
+    #[pyo3(get)]
+    pub name: String,
+}
+
+#[pymethods]
+impl Country {
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+#[pyclass]
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct Language{
+    #[pyo3(get)]
+    pub iso_639_1: String,
+    #[pyo3(get)]
+    pub name: String,
+}
+
+#[pymethods]
+impl Language {
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+// the listing queries stay free of extra joins, a correlated lookup of the linked ids is matched instead
+pub fn linked(table: &str, column: &str, key: &str, media_id: &str) -> String{
+    format!("(SELECT ','||GROUP_CONCAT({})||',' FROM {} WHERE {} = {})", column, table, key, media_id)
+}
+
+pub fn contains(value: &str) -> Option<String>{
+    Some(format!("%,{},%", value))
+}
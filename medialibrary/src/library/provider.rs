@@ -0,0 +1,136 @@
+use std::sync::{Arc, Mutex};
+
+use pyo3::prelude::*;
+
+use crate::rustmdb::{self, Error, discover::MediaKind, model::{Movie, MovieCollection, Person, SearchMovie, SearchTv, Tv, TvEpisode, TvSeason}};
+
+use super::{Error as LibraryError, ErrorKind};
+
+// a source of metadata for the library, results use the tmdb models and tmdb ids
+pub trait MetadataProvider: Send + Sync{
+    fn movie(&self, id: u64) -> Result<Movie, Error>;
+    fn collection(&self, id: u64) -> Result<MovieCollection, Error>;
+    fn tv(&self, id: u64) -> Result<Tv, Error>;
+    fn season(&self, tv_id: u64, season_number: u64) -> Result<TvSeason, Error>;
+    fn episode(&self, tv_id: u64, season_number: u64, episode_number: u64) -> Result<TvEpisode, Error>;
+    fn person(&self, id: u64) -> Result<Person, Error>;
+    fn search_movie(&self, query: &str, year: Option<u64>, language: Option<&str>) -> Result<Vec<SearchMovie>, Error>;
+    fn search_tv(&self, query: &str, year: Option<u64>, language: Option<&str>) -> Result<Vec<SearchTv>, Error>;
+
+    // only used to improve matching, sources without them keep the default
+    fn alternative_titles(&self, _kind: MediaKind, _id: u64) -> Result<Vec<String>, Error>{
+        Ok(Vec::new())
+    }
+
+    // (season, episode) in absolute order, empty when the source has no such order
+    fn absolute_order(&self, _tv_id: u64) -> Result<Vec<(u64, u64)>, Error>{
+        Ok(Vec::new())
+    }
+}
+
+pub struct TmdbProvider{
+}
+
+impl MetadataProvider for TmdbProvider{
+    fn movie(&self, id: u64) -> Result<Movie, Error>{
+        rustmdb::get_movie(id, false)
+    }
+
+    fn collection(&self, id: u64) -> Result<MovieCollection, Error>{
+        rustmdb::get_collection(id, false)
+    }
+
+    fn tv(&self, id: u64) -> Result<Tv, Error>{
+        rustmdb::get_tv(id, false)
+    }
+
+    fn season(&self, tv_id: u64, season_number: u64) -> Result<TvSeason, Error>{
+        rustmdb::get_tv_season(tv_id, season_number, false)
+    }
+
+    fn episode(&self, tv_id: u64, season_number: u64, episode_number: u64) -> Result<TvEpisode, Error>{
+        rustmdb::get_tv_episode(tv_id, season_number, episode_number, false)
+    }
+
+    fn person(&self, id: u64) -> Result<Person, Error>{
+        rustmdb::get_person(id, false)
+    }
+
+    fn search_movie(&self, query: &str, year: Option<u64>, language: Option<&str>) -> Result<Vec<SearchMovie>, Error>{
+        let mut search = rustmdb::search_movie(query);
+        if let Some(year) = year{
+            search.year(year);
+        }
+        if let Some(language) = language{
+            search.language(language);
+        }
+        Ok(search.request()?.results)
+    }
+
+    fn search_tv(&self, query: &str, year: Option<u64>, language: Option<&str>) -> Result<Vec<SearchTv>, Error>{
+        let mut search = rustmdb::search_tv(query);
+        if let Some(year) = year{
+            search.year(year);
+        }
+        if let Some(language) = language{
+            search.language(language);
+        }
+        Ok(search.request()?.results)
+    }
+
+    fn alternative_titles(&self, kind: MediaKind, id: u64) -> Result<Vec<String>, Error>{
+        Ok(rustmdb::get_alternative_titles(kind, id, false)?.titles.into_iter().map(|title| title.title).collect())
+    }
+
+    fn absolute_order(&self, tv_id: u64) -> Result<Vec<(u64, u64)>, Error>{
+        let groups = rustmdb::get_episode_groups(tv_id, false)?;
+        let summary = match groups.results.iter().find(|group| group.group_type == 2){
+            Some(summary) => summary,
+            None => return Ok(Vec::new()),
+        };
+        let mut group = rustmdb::get_episode_group(tv_id, &summary.id, false)?;
+        group.groups.sort_by_key(|part| part.order);
+        let mut order = Vec::new();
+        for mut part in group.groups{
+            part.episodes.sort_by_key(|episode| episode.order);
+            order.extend(part.episodes.iter().map(|episode| (episode.season_number, episode.episode_number)));
+        }
+        Ok(order)
+    }
+}
+
+lazy_static! {
+    pub static ref PROVIDER: Arc<Mutex<String>> = Arc::new(Mutex::new("tmdb".to_string()));
+}
+
+// new sources are added here
+pub fn names() -> Vec<String>{
+    vec!["tmdb".to_string()]
+}
+
+fn lookup(name: &str) -> Option<Arc<dyn MetadataProvider>>{
+    match name{
+        "tmdb" => Some(Arc::new(TmdbProvider{})),
+        _ => None,
+    }
+}
+
+pub fn set_default(name: &str) -> PyResult<()>{
+    if lookup(name).is_none(){
+        return Err(LibraryError::new(ErrorKind::Provider, format!("unknown provider {}", name), "set_provider").into())
+    }
+    *PROVIDER.lock().unwrap() = name.to_string();
+    Ok(())
+}
+
+// the named provider, or the library default when no name is given
+pub fn get(name: Option<&str>) -> PyResult<Arc<dyn MetadataProvider>>{
+    let name = match name{
+        Some(name) => name.to_string(),
+        None => PROVIDER.lock().unwrap().clone(),
+    };
+    match lookup(&name){
+        Some(provider) => Ok(provider),
+        None => Err(LibraryError::new(ErrorKind::Provider, format!("unknown provider {}", name), "provider").into()),
+    }
+}
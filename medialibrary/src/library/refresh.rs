@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use pyo3::prelude::*;
+
+use crate::database::DATABASE;
+use crate::rustmdb::cache::{self, CacheKind};
+use crate::rustmdb::changes::{changes, CHANGE_WINDOW_DAYS};
+
+use super::{update_db, provider::MetadataProvider, nfo::LOCAL_ID_BASE};
+
+#[pyclass]
+#[derive(Debug, Clone, Default)]
+pub struct RefreshReport{
+    #[pyo3(get)]
+    pub movies: Vec<u64>,
+    #[pyo3(get)]
+    pub tvs: Vec<u64>,
+    #[pyo3(get)]
+    pub persons: Vec<u64>,
+    #[pyo3(get)]
+    pub errors: Vec<String>,
+}
+
+#[pymethods]
+impl RefreshReport{
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+// rows older than max_age, plus the recent ones tmdb reports as changed since their oldest update
+fn stale_ids(table: &str, kind: &str, max_age: u64, use_changes: bool) -> PyResult<Vec<u64>>{
+    let ages = DATABASE.updated_ages(table, max_age, LOCAL_ID_BASE)?;
+    let mut changed = HashSet::new();
+    if use_changes{
+        if let Some(since) = DATABASE.oldest_update(table, max_age.min(CHANGE_WINDOW_DAYS), LOCAL_ID_BASE)?{
+            changed = changes(kind, &since[..10])?;
+        }
+    }
+    Ok(ages.into_iter().filter(|(id, old)| *old || changed.contains(id)).map(|(id, _)| id).collect())
+}
+
+fn refresh_movie(user: &String, provider: &dyn MetadataProvider, movie_id: u64) -> PyResult<()>{
+    cache::invalidate(CacheKind::Movie, Some(movie_id));
+    update_db::update_movie(user, provider, movie_id)
+}
+
+fn refresh_tv(user: &String, provider: &dyn MetadataProvider, tv_id: u64) -> PyResult<()>{
+    cache::invalidate(CacheKind::Tv, Some(tv_id));
+    cache::invalidate(CacheKind::Season, Some(tv_id));
+    cache::invalidate(CacheKind::Episode, Some(tv_id));
+    update_db::update_tv(user, provider, tv_id)?;
+    for season_number in DATABASE.episode_seasons(tv_id)?{
+        update_db::update_season(user, provider, tv_id, season_number)?;
+    }
+    Ok(())
+}
+
+fn refresh_person(provider: &dyn MetadataProvider, person_id: u64) -> PyResult<()>{
+    cache::invalidate(CacheKind::Person, Some(person_id));
+    update_db::update_person(provider, person_id)
+}
+
+pub fn refresh(user: &String, provider: &dyn MetadataProvider, max_age: u64, use_changes: bool) -> PyResult<RefreshReport>{
+    let mut report = RefreshReport::default();
+
+    for movie_id in stale_ids("Movies", "movie", max_age, use_changes)?{
+        match refresh_movie(user, provider, movie_id){
+            Ok(()) => report.movies.push(movie_id),
+            Err(e) => report.errors.push(format!("movie {}: {}", movie_id, e)),
+        }
+    }
+
+    for tv_id in stale_ids("Tvs", "tv", max_age, use_changes)?{
+        match refresh_tv(user, provider, tv_id){
+            Ok(()) => report.tvs.push(tv_id),
+            Err(e) => report.errors.push(format!("tv {}: {}", tv_id, e)),
+        }
+    }
+
+    for person_id in stale_ids("Persons", "person", max_age, use_changes)?{
+        match refresh_person(provider, person_id){
+            Ok(()) => report.persons.push(person_id),
+            Err(e) => report.errors.push(format!("person {}: {}", person_id, e)),
+        }
+    }
+
+    Ok(report)
+}
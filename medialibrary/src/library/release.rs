@@ -0,0 +1,378 @@
+use std::path::Path;
+
+use pyo3::prelude::*;
+use regex::Regex;
+
+use crate::rustmdb::title::normalize;
+
+#[pyclass]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReleaseName{
+    #[pyo3(get)]
+    pub title: String,
+    #[pyo3(get)]
+    pub year: Option<u64>,
+    #[pyo3(get)]
+    pub season: Option<u64>,
+    #[pyo3(get)]
+    pub episodes: Vec<u64>,
+    #[pyo3(get)]
+    pub absolute: Option<u64>,
+    #[pyo3(get)]
+    pub date: Option<String>,
+    #[pyo3(get)]
+    pub part: Option<u64>,
+    #[pyo3(get)]
+    pub edition: Option<String>,
+    #[pyo3(get)]
+    pub group: Option<String>,
+    #[pyo3(get)]
+    pub tags: Vec<String>,
+}
+
+#[pymethods]
+impl ReleaseName{
+    pub fn episode(&self) -> Option<u64>{
+        self.episodes.first().copied()
+    }
+
+    pub fn json(&self) -> PyResult<String>{
+        Ok(serde_json::to_string(self).unwrap())
+    }
+
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+// never part of a title, the first one ends it
+const TECHNICAL: [&str; 38] = [
+    "480p", "576p", "720p", "1080p", "1080i", "2160p", "4k", "uhd",
+    "bluray", "blu-ray", "bdrip", "brrip", "bdremux", "remux", "web-dl", "webdl", "webrip", "hdtv", "hdrip",
+    "dvdrip", "dvdscr", "dvd5", "dvd9", "tvrip", "pdtv",
+    "x264", "x265", "h264", "h265", "hevc", "xvid", "divx", "10bit", "8bit", "hdr10", "truehd",
+    "vostfr", "truefrench",
+];
+
+// real words too, "Charlotte's Web" or "French Kiss", only tags once the title is over
+const NOISE: [&str; 30] = [
+    "hd", "sd", "web", "dvd", "amzn", "nf", "dsnp", "hmax", "avc", "hdr", "dv", "atmos", "flac", "mp3", "multi", "subbed",
+    "proper", "repack", "internal", "limited", "dubbed", "french", "vf", "vff", "readnfo",
+    "aac", "ac3", "eac3", "dts", "ddp",
+];
+
+const EDITIONS: [(&str, &str); 13] = [
+    ("director's cut", "Director's Cut"),
+    ("directors cut", "Director's Cut"),
+    ("extended cut", "Extended"),
+    ("extended edition", "Extended"),
+    ("extended", "Extended"),
+    ("theatrical cut", "Theatrical"),
+    ("theatrical", "Theatrical"),
+    ("special edition", "Special Edition"),
+    ("ultimate edition", "Ultimate Edition"),
+    ("collector's edition", "Collector's Edition"),
+    ("unrated", "Unrated"),
+    ("uncut", "Uncut"),
+    ("remastered", "Remastered"),
+];
+
+lazy_static! {
+    static ref SEASON_EPISODE: Regex = Regex::new(r"^s(\d{1,2})e(\d{1,3})((?:-?e\d{1,3}|-\d{1,3})*)$").unwrap();
+    static ref CROSS: Regex = Regex::new(r"^(\d{1,2})x(\d{2,3})((?:-?x\d{2,3}|-\d{2,3})*)$").unwrap();
+    static ref SEASON: Regex = Regex::new(r"^s(\d{1,2})$").unwrap();
+    static ref EPISODE: Regex = Regex::new(r"^(?:e|ep)(\d{1,3})((?:-?e\d{1,3}|-\d{1,3})*)$").unwrap();
+    static ref MORE_EPISODES: Regex = Regex::new(r"(-?)[ex]?(\d{1,3})").unwrap();
+    static ref DATE: Regex = Regex::new(r"^((?:19|20)\d{2})-?(\d{2})-?(\d{2})$").unwrap();
+    static ref DAY_FIRST_DATE: Regex = Regex::new(r"^(\d{1,2})-(\d{1,2})-((?:19|20)\d{2})$").unwrap();
+    static ref DAY: Regex = Regex::new(r"^\d{1,2}$").unwrap();
+    static ref ABSOLUTE: Regex = Regex::new(r"^\d{1,4}$").unwrap();
+    static ref NUMBER: Regex = Regex::new(r"^\d{1,3}$").unwrap();
+    static ref YEAR: Regex = Regex::new(r"^(19\d{2}|20\d{2})$").unwrap();
+    static ref PART: Regex = Regex::new(r"^(?:cd|disc|disk|part|pt)(\d{1,2})$").unwrap();
+    static ref AUDIO: Regex = Regex::new(r"^(?:dd|ddp|dd\+|eac3|ac3|aac|dts|truehd)\d?$").unwrap();
+    static ref SEASON_FOLDER: Regex = Regex::new(r"(?i)^(?:season|saison|staffel|series|s)[ ._-]*(\d{1,2})$").unwrap();
+    static ref BRACKET: Regex = Regex::new(r"\[([^\]]*)\]").unwrap();
+}
+
+fn is_technical(token: &str) -> bool{
+    TECHNICAL.contains(&token) || (AUDIO.is_match(token) && token.ends_with(|c: char| c.is_ascii_digit()))
+}
+
+fn is_noise(token: &str) -> bool{
+    is_technical(token) || NOISE.contains(&token)
+}
+
+fn roman(token: &str) -> Option<u64>{
+    match token{
+        "i" => Some(1),
+        "ii" => Some(2),
+        "iii" => Some(3),
+        "iv" => Some(4),
+        "v" => Some(5),
+        _ => None,
+    }
+}
+
+fn iso_date(year: &str, month: &str, day: &str) -> Option<String>{
+    let (year, month, day): (u64, u64, u64) = (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+    if (1..=12).contains(&month) && (1..=31).contains(&day){
+        Some(format!("{}-{:02}-{:02}", year, month, day))
+    }
+    else{
+        None
+    }
+}
+
+// 01.05.2023 is read day first unless the day can only be the second number
+fn day_first_date(first: &str, second: &str, year: &str) -> Option<String>{
+    if second.parse::<u64>().ok()? > 12{
+        iso_date(year, first, second)
+    }
+    else{
+        iso_date(year, second, first)
+    }
+}
+
+// "2023.05.01", "2023-05-01", "20230501" or "01.05.2023" as 2023-05-01
+pub fn parse_date(text: &str) -> Option<String>{
+    let text = text.trim().replace(['.', '/', '_', ' '], "-");
+    if let Some(cap) = DATE.captures(&text){
+        return iso_date(&cap[1], &cap[2], &cap[3])
+    }
+    DAY_FIRST_DATE.captures(&text).and_then(|cap| day_first_date(&cap[1], &cap[2], &cap[3]))
+}
+
+// "e02e03", "-03" and "-e05" continue a first episode, a dash means a range
+fn more_episodes(first: u64, rest: &str) -> Vec<u64>{
+    let mut episodes = vec![first];
+    for cap in MORE_EPISODES.captures_iter(rest){
+        let number: u64 = cap[2].parse().unwrap();
+        let last = *episodes.last().unwrap();
+        if &cap[1] == "-" && number > last && number - last < 50{
+            episodes.extend(last + 1..=number);
+        }
+        else if !episodes.contains(&number){
+            episodes.push(number);
+        }
+    }
+    episodes
+}
+
+fn season_folder(name: &str) -> Option<u64>{
+    if name.eq_ignore_ascii_case("specials") || name.eq_ignore_ascii_case("special"){
+        return Some(0)
+    }
+    SEASON_FOLDER.captures(name.trim()).and_then(|cap| cap[1].parse().ok())
+}
+
+fn tokens(name: &str, release: &mut ReleaseName) -> Vec<String>{
+    let mut name = name.to_string();
+    if let Some(cap) = BRACKET.captures(&name){
+        if name.trim_start().starts_with('['){
+            release.group = Some(cap[1].trim().to_string()).filter(|group| !group.is_empty());
+            name = name.replacen(&cap[0], " ", 1);
+        }
+    }
+    name.chars()
+        .map(|c| match c{
+            '.' | '_' | '(' | ')' | '[' | ']' | '{' | '}' | ',' => ' ',
+            _ => c,
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .map(|token| token.to_string())
+        .collect()
+}
+
+// the words of a single file or folder name, without looking at the path
+pub fn parse_name(name: &str) -> ReleaseName{
+    let mut release = ReleaseName::default();
+    let words = tokens(name, &mut release);
+    let lower: Vec<String> = words.iter().map(|word| word.to_lowercase()).collect();
+
+    let mut end = words.len();
+    let mut years = Vec::new();
+    let mut index = 0;
+    while index < lower.len(){
+        let token = lower[index].as_str();
+        let next = lower.get(index + 1).map(|next| next.as_str());
+
+        let after = lower.get(index + 2).map(|after| after.as_str());
+
+        if let Some(date) = parse_date(token).filter(|_| release.date.is_none()){
+            release.date = Some(date);
+            end = end.min(index);
+        }
+        else if let Some(date) = next.zip(after)
+                .filter(|(next, after)| YEAR.is_match(token) && DAY.is_match(next) && DAY.is_match(after))
+                .and_then(|(month, day)| iso_date(token, month, day)){
+            release.date = Some(date);
+            end = end.min(index);
+            index += 2;
+        }
+        else if let Some(date) = next.zip(after)
+                .filter(|(next, after)| DAY.is_match(token) && DAY.is_match(next) && YEAR.is_match(after))
+                .and_then(|(day, year)| day_first_date(token, day, year)){
+            release.date = Some(date);
+            end = end.min(index);
+            index += 2;
+        }
+        else if let Some(cap) = SEASON_EPISODE.captures(token){
+            release.season = cap[1].parse().ok();
+            release.episodes = more_episodes(cap[2].parse().unwrap(), &cap[3]);
+            end = end.min(index);
+        }
+        else if let Some(cap) = CROSS.captures(token){
+            release.season = cap[1].parse().ok();
+            release.episodes = more_episodes(cap[2].parse().unwrap(), &cap[3]);
+            end = end.min(index);
+        }
+        else if let (Some(cap), Some(episode)) = (SEASON.captures(token), next.and_then(|next| EPISODE.captures(next))){
+            release.season = cap[1].parse().ok();
+            release.episodes = more_episodes(episode[1].parse().unwrap(), &episode[2]);
+            end = end.min(index);
+            index += 1;
+        }
+        else if (token == "season" || token == "saison" || token == "staffel") && next.is_some_and(|next| NUMBER.is_match(next)){
+            release.season = next.and_then(|next| next.parse().ok());
+            end = end.min(index);
+            index += 1;
+        }
+        else if (token == "episode" || token == "ep") && next.is_some_and(|next| NUMBER.is_match(next)){
+            release.episodes = vec![next.unwrap().parse().unwrap()];
+            end = end.min(index);
+            index += 1;
+        }
+        else if let Some(cap) = EPISODE.captures(token){
+            if release.episodes.is_empty(){
+                release.episodes = more_episodes(cap[1].parse().unwrap(), &cap[2]);
+            }
+            end = end.min(index);
+        }
+        // "Show - 37" is an absolute number unless a season folder, or a third digit, gives the season
+        else if token == "-" && release.episodes.is_empty() && next.is_some_and(|next| ABSOLUTE.is_match(next) && !YEAR.is_match(next)){
+            let number: u64 = next.unwrap().parse().unwrap();
+            // parse() splits it once the folders are known
+            release.absolute = Some(number);
+            release.episodes = vec![number];
+            end = end.min(index);
+            index += 1;
+        }
+        else if YEAR.is_match(token) && index > 0{
+            years.push(index);
+        }
+        else if let Some(cap) = PART.captures(token){
+            release.part = cap[1].parse().ok();
+            if !token.starts_with('p'){
+                end = end.min(index);
+            }
+        }
+        else if (token == "part" || token == "pt" || token == "cd" || token == "disc") && next.is_some_and(|next| NUMBER.is_match(next) || roman(next).is_some()){
+            release.part = next.and_then(|next| next.parse().ok().or_else(|| roman(next)));
+            if token == "cd" || token == "disc"{
+                end = end.min(index);
+            }
+            index += 1;
+        }
+        else if is_technical(token){
+            end = end.min(index);
+        }
+        // "x264-GROUP", "French-Canadian" stays a word
+        else if let Some((prefix, group)) = token.rsplit_once('-').filter(|(prefix, _)| is_technical(prefix)){
+            if !group.is_empty() && !is_noise(group){
+                release.group = Some(words[index][prefix.len() + 1..].to_string());
+            }
+            end = end.min(index);
+        }
+        index += 1;
+    }
+
+    // the last year before the title ends is the release year, earlier ones belong to the title,
+    // one right after the noise still counts
+    let noise_only = |from: usize, to: usize| from < to && lower[from..to].iter().all(|token| token.split('-').any(is_noise));
+    if let Some(&year) = years.iter().rev().find(|year| **year <= end || noise_only(end, **year)){
+        release.year = lower[year].parse().ok();
+        end = end.min(year);
+    }
+
+    // editions and tags come after the title, as whole words
+    release.edition = (end..lower.len())
+        .find_map(|start| EDITIONS.iter().find(|(pattern, _)| {
+            pattern.split(' ').enumerate().all(|(offset, word)| lower.get(start + offset).is_some_and(|token| token == word))
+        }))
+        .map(|(_, edition)| edition.to_string());
+    for token in &lower[end..]{
+        match token.rsplit_once('-'){
+            _ if is_noise(token) => release.tags.push(token.to_string()),
+            Some((prefix, group)) if is_noise(prefix) && is_noise(group) => release.tags.push(token.to_string()),
+            Some((prefix, _)) if is_noise(prefix) => release.tags.push(prefix.to_string()),
+            _ => (),
+        }
+    }
+
+    // a part only counts once the title is over, "Deathly Hallows Part 2" stays a title
+    if lower[..end].iter().any(|token| token == "part" || token == "pt" || PART.is_match(token)){
+        release.part = None;
+    }
+
+    release.title = words[..end].join(" ").trim_matches(|c: char| c == '-' || c.is_whitespace()).to_string();
+    release
+}
+
+// the file name first, then the season and show folders for what it lacks
+pub fn parse(path: &str) -> ReleaseName{
+    let path = Path::new(path);
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let mut release = parse_name(&stem);
+
+    let folders = path.parent().into_iter().flat_map(|parent| parent.ancestors()).take(3);
+    for folder in folders{
+        let name = match folder.file_name(){
+            Some(name) => name.to_string_lossy().to_string(),
+            None => break,
+        };
+        if let Some(season) = season_folder(&name){
+            if release.season.is_none(){
+                release.season = Some(season);
+                // under "Season 2" a "- 05" or "- 205" is episode 5 of that season, not an absolute number
+                if let Some(number) = release.absolute.take(){
+                    if number >= 100 && number / 100 == season{
+                        release.episodes = vec![number % 100];
+                    }
+                }
+            }
+            continue
+        }
+
+        // "Episode 2.mkv" only names the episode, the show is the folder
+        let show = parse_name(&name);
+        if release.title.is_empty(){
+            release.title = show.title.clone();
+        }
+        if release.season.is_none(){
+            release.season = show.season;
+        }
+        if normalize(&show.title) == normalize(&release.title){
+            release.year = release.year.or(show.year);
+            release.edition = release.edition.or(show.edition);
+        }
+        break
+    }
+
+    // without a season folder, "Show - 102" is season 1 episode 2
+    if let Some(number) = release.absolute.filter(|number| release.season.is_none() && (100..1000).contains(number)){
+        release.season = Some(number / 100);
+        release.episodes = vec![number % 100];
+        release.absolute = None;
+    }
+    // an episode without any season is counted from the start of the show
+    if release.season.is_none() && release.episodes.len() == 1{
+        release.absolute = release.absolute.or(release.episode());
+    }
+    release
+}
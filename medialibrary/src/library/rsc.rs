@@ -0,0 +1,72 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use image::{io::Reader, imageops::FilterType, DynamicImage, ImageFormat};
+
+use super::RSCPATH;
+
+lazy_static! {
+    pub static ref RSC_SIZES: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(vec![185, 500]));
+}
+
+pub fn set_sizes(mut sizes: Vec<u32>){
+    sizes.retain(|width| *width > 0);
+    sizes.sort_unstable();
+    sizes.dedup();
+    *RSC_SIZES.lock().unwrap() = sizes;
+}
+
+pub fn size_name(width: u32) -> String{
+    format!("w{}", width)
+}
+
+pub fn file(size: &str, rsc_path: &str) -> PathBuf{
+    PathBuf::from(format!("{}/{}{}", *RSCPATH.lock().unwrap(), size, rsc_path))
+}
+
+// builds the variant from the original, never upscales
+pub fn resize(rsc_path: &str, width: u32) -> bool{
+    let original = match Reader::open(file("original", rsc_path)).and_then(|reader| reader.with_guessed_format()){
+        Ok(reader) => match reader.decode(){
+            Ok(original) => original,
+            Err(_) => return false,
+        },
+        Err(_) => return false,
+    };
+    if original.width() <= width{
+        return false
+    }
+    let height = (original.height() as u64 * width as u64 / original.width() as u64).max(1) as u32;
+    let path = file(&size_name(width), rsc_path);
+    if let Some(parent) = path.parent(){
+        if fs::create_dir_all(parent).is_err(){
+            return false
+        }
+    }
+    let resized = original.resize_exact(width, height, FilterType::Lanczos3);
+    match ImageFormat::from_path(&path){
+        Ok(ImageFormat::Jpeg) => DynamicImage::ImageRgb8(resized.to_rgb8()).save(path).is_ok(),
+        _ => resized.save(path).is_ok(),
+    }
+}
+
+// smallest cached variant at least `width` wide, else the original, else the largest variant
+pub fn best(rsc_path: &str, width: Option<u32>) -> Option<String>{
+    if rsc_path.is_empty(){
+        return None
+    }
+    let sizes = RSC_SIZES.lock().unwrap().clone();
+    let mut candidates = Vec::new();
+    if let Some(width) = width{
+        candidates.extend(sizes.iter().filter(|size| **size >= width).map(|size| size_name(*size)));
+    }
+    candidates.push("original".to_string());
+    candidates.extend(sizes.iter().rev().map(|size| size_name(*size)));
+    for size in candidates{
+        if file(&size, rsc_path).exists(){
+            return Some(format!("{}{}", size, rsc_path))
+        }
+    }
+    None
+}
@@ -9,6 +9,7 @@ use super::cast::Crew;
 use super::collection::CollectionResult;
 use super::collection::CollectionSearch;
 use super::keyword::Keyword;
+use super::production::{self, Company, Country, Language, Network};
 use super::trailer::Trailer;
 use super::video::VideoResult;
 use super::video::VideoSearch;
@@ -50,6 +51,10 @@ pub struct Tv{
     #[pyo3(get)]
     pub episode_run_time: u64,
     #[pyo3(get)]
+    pub imdb_id: String,
+    #[pyo3(get)]
+    pub tvdb_id: String,
+    #[pyo3(get)]
     pub adding: String,
     #[pyo3(get)]
     pub seasons: Vec<Season>,
@@ -64,6 +69,18 @@ pub struct Tv{
     #[pyo3(get)]
     pub collection: Vec<CollectionResult>,
     #[pyo3(get)]
+    pub companies: Vec<Company>,
+    #[pyo3(get)]
+    pub networks: Vec<Network>,
+    #[pyo3(get)]
+    pub countries: Vec<Country>,
+    #[pyo3(get)]
+    pub origin_countries: Vec<Country>,
+    #[pyo3(get)]
+    pub languages: Vec<Language>,
+    #[pyo3(get)]
+    pub creators: Vec<Crew>,
+    #[pyo3(get)]
     pub watched: u64,
     #[pyo3(get)]
     pub updated: String,
@@ -80,6 +97,7 @@ impl Tv{
     pub fn set_persons(&mut self) -> PyResult<()>{
         self.cast = DATABASE.get_tv_cast(&self.user, self.id)?;
         self.crew = DATABASE.get_tv_crew(&self.user, self.id)?;
+        self.creators = self.crew.iter().filter(|crew| crew.job == "Creator").cloned().collect();
         Ok(())
     }
 
@@ -93,6 +111,15 @@ impl Tv{
         Ok(())
     }
 
+    pub fn set_production(&mut self) -> PyResult<()>{
+        self.companies = DATABASE.get_companies("Tv", self.id)?;
+        self.networks = DATABASE.get_networks(self.id)?;
+        self.countries = DATABASE.get_countries("Tv", self.id)?;
+        self.origin_countries = DATABASE.get_origin_countries(self.id)?;
+        self.languages = DATABASE.get_languages("Tv", self.id)?;
+        Ok(())
+    }
+
     pub fn set_collection(&mut self) -> PyResult<()>{
         self.collection = CollectionSearch::new(&self.user).tv(self.id)?.results(None, None)?;
         Ok(())
@@ -215,10 +242,39 @@ impl TvSearch{
         self.find("TvCrews.person_id", "=", Some(person_id.to_string()))
     }
 
+    pub fn imdb_id(&mut self, imdb_id: String) -> PyResult<TvSearch>{
+        self.find("Tvs.imdb_id", "=", Some(imdb_id))
+    }
+
+    pub fn tvdb_id(&mut self, tvdb_id: u64) -> PyResult<TvSearch>{
+        self.find("Tvs.tvdb_id", "=", Some(tvdb_id.to_string()))
+    }
+
     pub fn collection(&mut self, collection_id: u64) -> PyResult<TvSearch>{
         self.find("TvCollectionLinks.collection_id", "=", Some(collection_id.to_string()))
     }
 
+    pub fn creator(&mut self, person_id: u64) -> PyResult<TvSearch>{
+        self.find("TvCrews.job", "=", Some("Creator".to_string()))?;
+        self.crew(person_id)
+    }
+
+    pub fn company(&mut self, company_id: u64) -> PyResult<TvSearch>{
+        self.find(&production::linked("TvCompanyLinks", "company_id", "tv_id", "Tvs.id"), "LIKE", production::contains(&company_id.to_string()))
+    }
+
+    pub fn network(&mut self, network_id: u64) -> PyResult<TvSearch>{
+        self.find(&production::linked("TvNetworkLinks", "network_id", "tv_id", "Tvs.id"), "LIKE", production::contains(&network_id.to_string()))
+    }
+
+    pub fn country(&mut self, iso_3166_1: &str) -> PyResult<TvSearch>{
+        self.find(&production::linked("TvOriginCountries", "country", "tv_id", "Tvs.id"), "LIKE", production::contains(iso_3166_1))
+    }
+
+    pub fn spoken_language(&mut self, iso_639_1: &str) -> PyResult<TvSearch>{
+        self.find(&production::linked("TvLanguageLinks", "language", "tv_id", "Tvs.id"), "LIKE", production::contains(iso_639_1))
+    }
+
     pub fn find(&mut self, column: &str, operator: &str, value: Option<String>) -> PyResult<TvSearch>{
         if let Some(value) = value {
             self.parameters.insert(column.to_string(), Some((operator.to_string(), value)));
@@ -388,6 +444,11 @@ impl EpisodeSearch{
         self.find("EpisodeCrews.person_id", "=", Some(person_id.to_string()))
     }
 
+    pub fn guest(&mut self, person_id: u64) -> PyResult<EpisodeSearch>{
+        self.find("EpisodeCasts.guest", "=", Some("1".to_string()))?;
+        self.cast(person_id)
+    }
+
     pub fn order_by(&mut self, order_by: String) -> PyResult<EpisodeSearch>{
         self.order_by = Some(order_by);
         Ok(self.clone())
@@ -443,6 +504,12 @@ pub struct Episode{
     #[pyo3(get)]
     pub vote_count: u64,
     #[pyo3(get)]
+    pub imdb_id: String,
+    #[pyo3(get)]
+    pub tvdb_id: String,
+    #[pyo3(get)]
+    pub still_path: String,
+    #[pyo3(get)]
     pub video: Vec<VideoResult>,
     #[pyo3(get)]
     pub tv: Option<Tv>,
@@ -476,8 +543,16 @@ impl Episode{
         Ok(())
     }
 
+    // a shared file starts at an even share of its duration for each episode it covers
     pub fn set_videos(&mut self) -> PyResult<()>{
-        self.video = VideoSearch::new(&self.user).tv()?.media_id(self.id)?.results(None, None)?;
+        self.video = VideoSearch::new(&self.user).tv()?.episode(self.id)?.results(None, None)?;
+        for video in self.video.iter_mut(){
+            let episodes = DATABASE.get_video_episodes(video.id)?;
+            if episodes.len() > 1{
+                let position = episodes.iter().position(|id| *id == self.id).unwrap_or(0) as u64;
+                video.start = Some(video.duration * position / episodes.len() as u64);
+            }
+        }
         Ok(())
     }
 
@@ -498,11 +573,12 @@ impl Episode{
     }
 
     pub fn delete(&mut self) -> PyResult<()>{
-        if VideoSearch::new(&self.user).tv()?.media_id(self.id)?.exist()?{
+        if VideoSearch::new(&self.user).tv()?.episode(self.id)?.exist()?{
             return Ok(())
         }
 
         self.set_tv()?;
+        self.set_persons()?;
 
         DATABASE.delete_episode(self.id)?;
 
@@ -510,7 +586,12 @@ impl Episode{
             tv.delete()?;
         }
 
-        //todo person remove for episode
+        // guest stars are only linked to the episode, the main credits go with the tv
+        for cast in self.cast.iter().filter(|cast| cast.guest){
+            if let Some(mut person) = DATABASE.get_person(&self.user, cast.id)?{
+                person.delete()?;
+            }
+        }
 
         Ok(())
     }
@@ -1,74 +1,141 @@
 use std::io;
-use std::fs::File;
+use std::fs::{self, File};
 
 use pyo3::prelude::*;
 use pyo3::exceptions::PyReferenceError;
 
-use crate::{rustmdb::{get_movie, get_person, get_tv, get_tv_episode}, database::DATABASE};
+use crate::{rustmdb::{IMAGE_URL, Error, client::download}, database::DATABASE};
 
-use super::{RSCPATH, movie::MovieSearch, tv::{TvSearch, EpisodeSearch}, cast::PersonSearch};
+use super::{RSCPATH, LANGUAGES, rsc, pool, provider::MetadataProvider, movie::MovieSearch, tv::{TvSearch, EpisodeSearch}, cast::PersonSearch};
 
 
-pub fn create_movie(user: &String, movie_id: u64) -> PyResult<()>{
-    if MovieSearch::new(user).id(movie_id)?.exist()?{
+pub fn create_movie(user: &String, provider: &dyn MetadataProvider, movie_id: u64) -> PyResult<()>{
+    // skeletons from an nfo import are replaced by the full tmdb entry
+    if MovieSearch::new(user).id(movie_id)?.exist()? && DATABASE.skeletons("Movies", &[movie_id])?.is_empty(){
         return Ok(())
     }
-    let movie = get_movie(movie_id)?;
-    let (person_ids, rsc_paths) = DATABASE.create_movie(&movie)?;
-    for person_id in person_ids{
-        create_person(user, person_id)?;
-    }
-    for rsc_path in rsc_paths{
-        update_rsc(&rsc_path)?;
+    update_movie(user, provider, movie_id)
+}
+
+pub fn update_movie(user: &String, provider: &dyn MetadataProvider, movie_id: u64) -> PyResult<()>{
+    let mut movie = provider.movie(movie_id)?;
+    movie.translations.retain_languages(&LANGUAGES.lock().unwrap());
+    let (person_ids, mut rsc_paths) = DATABASE.create_movie(&movie)?;
+    rsc_paths.extend(create_persons(user, provider, person_ids)?);
+    update_rscs(rsc_paths)?;
+    if let Some(collection) = &movie.belongs_to_collection{
+        match DATABASE.get_official_collection_id(collection.id)?{
+            Some(collection_id) => DATABASE.link_official_collection(collection_id, movie.id)?,
+            // the movie is stored already, a failed collection only stays missing until the next refresh
+            None => { update_collection(provider, collection.id).ok(); },
+        }
     }
 
     Ok(())
 }
 
+pub fn update_collection(provider: &dyn MetadataProvider, tmdb_id: u64) -> PyResult<()>{
+    let collection = provider.collection(tmdb_id)?;
+    let (_collection_id, rsc_paths) = DATABASE.create_official_collection(&collection)?;
+    update_rscs(rsc_paths)
+}
 
-pub fn create_person(user: &String, person_id: u64) -> PyResult<()>{
-    if PersonSearch::new(user).id(person_id)?.exist()?{
-        return Ok(())
+
+// fetches the missing persons on the worker pool, stores them here and returns their artwork
+pub fn create_persons(user: &String, provider: &dyn MetadataProvider, mut person_ids: Vec<u64>) -> PyResult<Vec<String>>{
+    person_ids.sort_unstable();
+    person_ids.dedup();
+    let mut missing = DATABASE.skeletons("Persons", &person_ids)?.into_iter().collect::<Vec<u64>>();
+    for person_id in person_ids{
+        if !PersonSearch::new(user).id(person_id)?.exist()?{
+            missing.push(person_id);
+        }
     }
-    let person = get_person(person_id)?;
-    let (_person_ids, rsc_paths) = DATABASE.create_person(&person)?;
-    for rsc_path in rsc_paths{
-        update_rsc(&rsc_path)?;
+
+    let mut rsc_paths = Vec::new();
+    for person in pool::map(missing, |person_id| provider.person(person_id)){
+        let (_person_ids, person_rsc_paths) = DATABASE.create_person(&person?)?;
+        rsc_paths.extend(person_rsc_paths);
+    }
+    Ok(rsc_paths)
+}
+
+pub fn update_rscs(mut rsc_paths: Vec<String>) -> PyResult<()>{
+    rsc_paths.sort_unstable();
+    rsc_paths.dedup();
+    for result in pool::map(rsc_paths, |rsc_path| update_rsc(&rsc_path)){
+        result?;
     }
     Ok(())
 }
 
-pub fn create_tv(user: &String, tv_id: u64, season_number: u64) -> PyResult<()>{
+// downloads the referenced artwork that never made it to the cache
+pub fn update_missing_rscs(mut rsc_paths: Vec<String>) -> PyResult<Vec<String>>{
+    rsc_paths.retain(|rsc_path| !rsc::file("original", rsc_path).exists());
+    update_rscs(rsc_paths.clone())?;
+    Ok(rsc_paths)
+}
+
+pub fn update_person(provider: &dyn MetadataProvider, person_id: u64) -> PyResult<()>{
+    let person = provider.person(person_id)?;
+    let (_person_ids, rsc_paths) = DATABASE.create_person(&person)?;
+    update_rscs(rsc_paths)
+}
+
+pub fn create_tv(user: &String, provider: &dyn MetadataProvider, tv_id: u64, season_number: u64) -> PyResult<()>{
     if let Some(result) = TvSearch::new(user).id(tv_id)?.last()?{
+        if !DATABASE.skeletons("Tvs", &[tv_id])?.is_empty(){
+            return update_tv(user, provider, tv_id)
+        }
         let tv = result.full()?;
         if let Some(season)  = tv.season(season_number)?{
             return Ok(())
         }
     }
-    let tv = get_tv(tv_id)?;
-    let (person_ids, rsc_paths) = DATABASE.create_tv(&tv)?;
-    for person_id in person_ids{
-        create_person(user, person_id)?;
-    }
-    for rsc_path in rsc_paths{
-        update_rsc(&rsc_path)?;
-    }
-    Ok(())
+    update_tv(user, provider, tv_id)
+}
+
+pub fn update_tv(user: &String, provider: &dyn MetadataProvider, tv_id: u64) -> PyResult<()>{
+    let mut tv = provider.tv(tv_id)?;
+    tv.translations.retain_languages(&LANGUAGES.lock().unwrap());
+    let (person_ids, mut rsc_paths) = DATABASE.create_tv(&tv)?;
+    rsc_paths.extend(create_persons(user, provider, person_ids)?);
+    update_rscs(rsc_paths)
+}
+
+pub fn import_season(user: &String, provider: &dyn MetadataProvider, tv_id: u64, season_number: u64) -> PyResult<()>{
+    create_tv(user, provider, tv_id, season_number)?;
+    update_season(user, provider, tv_id, season_number)
 }
 
-pub fn create_episode(user: &String, tv_id: u64, season_number: u64, episode_number: u64) -> PyResult<u64>{
-    if let Some(episode) = EpisodeSearch::new(user).tv(tv_id)?.season(season_number)?.episode(episode_number)?.last()?{
-        return Ok(episode.id)
+pub fn update_season(user: &String, provider: &dyn MetadataProvider, tv_id: u64, season_number: u64) -> PyResult<()>{
+    let season = provider.season(tv_id, season_number)?;
+    let (person_ids, mut rsc_paths) = DATABASE.create_season(tv_id, &season)?;
+    rsc_paths.extend(create_persons(user, provider, person_ids)?);
+    update_rscs(rsc_paths)
+}
+
+pub fn create_episode(user: &String, provider: &dyn MetadataProvider, tv_id: u64, season_number: u64, episode_number: u64) -> PyResult<u64>{
+    // a show from an nfo import goes through the tmdb season even when the episode is stored
+    let skeleton = !DATABASE.skeletons("Tvs", &[tv_id])?.is_empty();
+    if !skeleton{
+        if let Some(episode) = EpisodeSearch::new(user).tv(tv_id)?.season(season_number)?.episode(episode_number)?.last()?{
+            return Ok(episode.id)
+        }
     }
-    create_tv(user, tv_id, season_number)?;
-    let episode = get_tv_episode(tv_id, season_number, episode_number)?;
-    let (person_ids, rsc_paths) = DATABASE.create_episode(tv_id, &episode)?;
-    for person_id in person_ids{
-        create_person(user, person_id)?;
+    if skeleton || DATABASE.get_episode_id(tv_id, season_number, episode_number)?.is_none(){
+        import_season(user, provider, tv_id, season_number)?;
     }
-    for rsc_path in rsc_paths{
-        update_rsc(&rsc_path)?;
+    // a season only lists crew and guests, the episode itself is fetched once for its cast and external ids
+    if let Some(episode_id) = DATABASE.get_episode_id(tv_id, season_number, episode_number)?{
+        if !DATABASE.episode_lacks_details(episode_id)?{
+            return Ok(episode_id)
+        }
     }
+    let episode = provider.episode(tv_id, season_number, episode_number)?;
+    let (person_ids, mut rsc_paths) = DATABASE.create_episode(tv_id, &episode)?;
+    rsc_paths.extend(create_persons(user, provider, person_ids)?);
+    update_rscs(rsc_paths)?;
     Ok(episode.id)
 }
 
@@ -77,8 +144,10 @@ pub fn update_rsc(rsc_path: &str) -> PyResult<()>{
         return Ok(())
     }
 
-    let resp = match reqwest::blocking::get("https://image.tmdb.org/t/p/original".to_string() + rsc_path){
-        Ok(resp) => resp.bytes().unwrap(),
+    let url = format!("{}/original{}", *IMAGE_URL.lock().unwrap(), rsc_path);
+    let location = format!("rsc({})", rsc_path);
+    let resp = match download(&url, &location).and_then(|resp| resp.bytes().map_err(|e| Error::from_reqwest(e, &location))){
+        Ok(resp) => resp,
         Err(e) => return Err(PyReferenceError::new_err(format!("reqwest error getting poster path {}", e))),
     };
     
@@ -86,5 +155,31 @@ pub fn update_rsc(rsc_path: &str) -> PyResult<()>{
 
     io::copy(&mut resp.as_ref(), &mut out)?;
 
+    let sizes = rsc::RSC_SIZES.lock().unwrap().clone();
+    for width in sizes{
+        let size = rsc::size_name(width);
+        let path = rsc::file(&size, rsc_path);
+        if path.exists(){
+            continue
+        }
+        let url = format!("{}/{}{}", *IMAGE_URL.lock().unwrap(), size, rsc_path);
+        match download(&url, &format!("rsc({} {})", size, rsc_path)){
+            Ok(resp) if resp.status().is_success() => {
+                if let Some(parent) = path.parent(){
+                    fs::create_dir_all(parent)?;
+                }
+                let resp = match resp.bytes(){
+                    Ok(resp) => resp,
+                    Err(e) => return Err(PyReferenceError::new_err(format!("reqwest error getting poster path {}", e))),
+                };
+                let mut out = File::create(&path)?;
+                io::copy(&mut resp.as_ref(), &mut out)?;
+            },
+            _ => {
+                rsc::resize(rsc_path, width);
+            }
+        }
+    }
+
     Ok(())
 }
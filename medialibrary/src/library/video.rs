@@ -2,13 +2,14 @@ use std::collections::HashMap;
 
 use pyo3::types::PyList;
 use pyo3::{prelude::*, types::PyTuple};
-use regex::Regex;
 
 use crate::database::DATABASE;
+use crate::rustmdb::find::find;
 
 use super::movie::Movie;
+use super::release::{self, ReleaseName};
 use super::tv::{Episode, EpisodeSearch};
-use super::update_db::{create_movie, create_episode};
+use super::{nfo, numbering::{self, EpisodeMapping}, provider, export::{Exporter, ExportReport}, update_db::{create_movie, create_episode}};
 use super::{Error, ErrorKind};
 
 #[pyclass]
@@ -50,28 +51,33 @@ pub struct Video{
 #[pymethods]
 impl Video{
 
+    pub fn parse(&self) -> ReleaseName{
+        release::parse(&self.path)
+    }
+
     pub fn parse_tv(&self) -> PyResult<(String, u64, u64)>{
-        let re = Regex::new(r".*[/](.*)[.][sS](\d+)[eE](\d+)[.]?.*[.](.*)").unwrap();
-        for cap in re.captures_iter(&self.path) {
-            return Ok((cap[1].to_string().replace(".", " "), cap[2].parse::<u64>()?, cap[3].parse::<u64>()?))
+        let release = self.parse();
+        match (release.season, release.episode()){
+            (Some(season), Some(episode)) if !release.title.is_empty() => Ok((release.title, season, episode)),
+            _ => Err(Error::new(ErrorKind::ParseName, "could not parse name".to_string(), &format!("tv path: {}", self.path)).into()),
         }
-        return Err(Error::new(ErrorKind::ParseName, "could not parse name".to_string(), &format!("tv path: {}", self.path)).into())
     }
 
-    pub fn parse_movie(&self) -> PyResult<(String, u64)>{
-        let re = Regex::new(r".*[/](.*)[.](\d{4})[.]?.*[.](.*)").unwrap();
-        for cap in re.captures_iter(&self.path) {
-            return Ok((cap[1].to_string().replace(".", " "), cap[2].parse::<u64>()?))
+    pub fn parse_movie(&self) -> PyResult<(String, Option<u64>)>{
+        let release = self.parse();
+        if release.title.is_empty(){
+            return Err(Error::new(ErrorKind::ParseName, "could not parse name".to_string(), &format!("movie path: {}", self.path)).into())
         }
-        return Err(Error::new(ErrorKind::ParseName, "could not parse name".to_string(), &format!("movie path: {}", self.path)).into())
+        Ok((release.title, release.year))
     }
 
-    pub fn set_movie(&mut self, movie_id: u64) -> PyResult<()>{
+    #[args(provider = "None")]
+    pub fn set_movie(&mut self, movie_id: u64, provider: Option<&str>) -> PyResult<()>{
         if self.media_type != 0{
             return Err(Error::new(ErrorKind::MediaType,"mediatype error".to_string(),&format!("media type not movie {}", self.media_type)).into())
         }
 
-        create_movie(&self.user, movie_id)?;
+        create_movie(&self.user, provider::get(provider)?.as_ref(), movie_id)?;
 
         DATABASE.edit_video_media_id(self.id, movie_id)?;
 
@@ -84,24 +90,142 @@ impl Video{
         Ok(())
     }
 
-    pub fn set_tv(&mut self, tv_id: u64, season: u64, episode: u64) -> PyResult<()>{
+    #[args(provider = "None")]
+    pub fn set_tv(&mut self, tv_id: u64, season: u64, episode: u64, provider: Option<&str>) -> PyResult<()>{
+        self.set_tv_episodes(tv_id, season, vec![episode], provider)
+    }
+
+    #[args(provider = "None")]
+    pub fn set_tv_episodes(&mut self, tv_id: u64, season: u64, episodes: Vec<u64>, provider: Option<&str>) -> PyResult<()>{
         if self.media_type != 1{
             return Err(Error::new(ErrorKind::MediaType,"mediatype error".to_string(),&format!("media type not episode {}", self.media_type)).into())
         }
+        if episodes.is_empty(){
+            return Err(Error::new(ErrorKind::NotFound, "no episode number".to_string(), &format!("set_tv_episodes: {}", self.path)).into())
+        }
+
+        let provider = provider::get(provider)?;
+        let mut episode_ids = Vec::new();
+        for episode in episodes{
+            episode_ids.push(create_episode(&self.user, provider.as_ref(), tv_id, season, episode)?);
+        }
 
-        let episode_id = create_episode(&self.user, tv_id, season, episode)?;
+        let old_episodes = self.tv_episodes()?;
 
-        DATABASE.edit_video_media_id(self.id, episode_id)?;
+        DATABASE.set_video_episodes(self.id, &episode_ids)?;
 
-        if let Some(epiosde) = &mut self.tv_episode()?{
-            epiosde.delete()?;
+        // delete keeps the episodes still linked to a video
+        for mut episode in old_episodes{
+            episode.delete()?;
         }
 
-        self.media_id = Some(episode_id);
+        self.media_id = episode_ids.first().copied();
 
         Ok(())
     }
 
+    #[args(absolute = "None", provider = "None")]
+    pub fn resolve_absolute(&self, tv_id: u64, absolute: Option<u64>, provider: Option<&str>) -> PyResult<EpisodeMapping>{
+        let absolute = match absolute.or(self.parse().absolute){
+            Some(absolute) => absolute,
+            None => return Err(Error::new(ErrorKind::ParseName, "no absolute number".to_string(), &format!("tv path: {}", self.path)).into()),
+        };
+        numbering::resolve_absolute(provider::get(provider)?.as_ref(), tv_id, absolute)
+    }
+
+    #[args(absolute = "None", provider = "None")]
+    pub fn set_tv_absolute(&mut self, tv_id: u64, absolute: Option<u64>, provider: Option<&str>) -> PyResult<EpisodeMapping>{
+        let mapping = self.resolve_absolute(tv_id, absolute, provider)?;
+        self.set_tv(tv_id, mapping.season, mapping.episode, provider)?;
+        Ok(mapping)
+    }
+
+    #[args(date = "None", provider = "None")]
+    pub fn resolve_date(&self, tv_id: u64, date: Option<&str>, provider: Option<&str>) -> PyResult<EpisodeMapping>{
+        let date = match date{
+            Some(date) => release::parse_date(date),
+            None => self.parse().date,
+        };
+        match date{
+            Some(date) => numbering::resolve_date(provider::get(provider)?.as_ref(), tv_id, &date),
+            None => Err(Error::new(ErrorKind::ParseName, "no air date".to_string(), &format!("tv path: {}", self.path)).into()),
+        }
+    }
+
+    #[args(date = "None", provider = "None")]
+    pub fn set_tv_by_date(&mut self, tv_id: u64, date: Option<&str>, provider: Option<&str>) -> PyResult<EpisodeMapping>{
+        let mapping = self.resolve_date(tv_id, date, provider)?;
+        self.set_tv(tv_id, mapping.season, mapping.episode, provider)?;
+        Ok(mapping)
+    }
+
+    pub fn nfo_path(&self) -> PyResult<Option<String>>{
+        let path = std::path::Path::new(&self.path);
+        let found = match self.media_type{
+            0 => nfo::movie_path(path)?,
+            _ => nfo::episode_path(path).or_else(|| nfo::tv_path(path)),
+        };
+        Ok(found.map(|found| found.display().to_string()))
+    }
+
+    // links the video from its sidecar nfo files without touching the network
+    pub fn import_nfo(&mut self) -> PyResult<()>{
+        let media_id = match self.media_type{
+            0 => nfo::import_movie(&self.path)?,
+            1 => nfo::import_episode(&self.path, self.parse_tv().ok().map(|(_, season, episode)| (season, episode)))?,
+            _ => return Err(Error::new(ErrorKind::MediaType,"mediatype error".to_string(),&format!("media type unknown {}", self.media_type)).into()),
+        };
+
+        if self.media_type == 0{
+            DATABASE.edit_video_media_id(self.id, media_id)?;
+            if self.media_id != Some(media_id){
+                if let Some(movie) = &mut self.movie()?{
+                    movie.delete()?;
+                }
+            }
+        }
+        else{
+            let old_episodes = self.tv_episodes()?;
+            DATABASE.set_video_episodes(self.id, &[media_id])?;
+            // delete keeps the episodes still linked to a video
+            for mut episode in old_episodes{
+                episode.delete()?;
+            }
+        }
+
+        self.media_id = Some(media_id);
+
+        Ok(())
+    }
+
+    #[args(dry_run = "false", skip_existing = "true")]
+    pub fn export_nfo(&self, dry_run: bool, skip_existing: bool) -> PyResult<ExportReport>{
+        let mut exporter = Exporter::new(dry_run, skip_existing);
+        exporter.video(self)?;
+        Ok(exporter.report)
+    }
+
+    pub fn set_movie_by_imdb(&mut self, imdb_id: &str) -> PyResult<()>{
+        let found = find("imdb_id", imdb_id)?;
+        match found.movie_results.first(){
+            Some(movie) => self.set_movie(movie.id, None),
+            None => Err(Error::new(ErrorKind::NotFound, format!("no movie for imdb id {}", imdb_id), "set_movie_by_imdb").into()),
+        }
+    }
+
+    #[args(season = "None", episode = "None")]
+    pub fn set_tv_by_tvdb(&mut self, tvdb_id: u64, season: Option<u64>, episode: Option<u64>) -> PyResult<()>{
+        let found = find("tvdb_id", &tvdb_id.to_string())?;
+        if let Some(found_episode) = found.tv_episode_results.first(){
+            return self.set_tv(found_episode.show_id, found_episode.season_number, found_episode.episode_number, None)
+        }
+        match (found.tv_results.first(), season, episode){
+            (Some(tv), Some(season), Some(episode)) => self.set_tv(tv.id, season, episode, None),
+            (Some(_), _, _) => Err(Error::new(ErrorKind::NotFound, format!("tvdb id {} is a show, season and episode are required", tvdb_id), "set_tv_by_tvdb").into()),
+            (None, _, _) => Err(Error::new(ErrorKind::NotFound, format!("no tv for tvdb id {}", tvdb_id), "set_tv_by_tvdb").into()),
+        }
+    }
+
     pub fn movie(&self) -> PyResult<Option<Movie>>{
         if self.media_type != 0{
             return Err(Error::new(ErrorKind::MediaType,"mediatype error".to_string(),&format!("media type not movie {}", self.media_type)).into())
@@ -114,6 +238,19 @@ impl Video{
         }
     }
 
+    pub fn tv_episodes(&self) -> PyResult<Vec<Episode>>{
+        if self.media_type != 1{
+            return Err(Error::new(ErrorKind::MediaType,"mediatype error".to_string(),&format!("media type not episode {}", self.media_type)).into())
+        }
+        let mut episodes = Vec::new();
+        for episode_id in DATABASE.get_video_episodes(self.id)?{
+            if let Some(episode) = EpisodeSearch::new(&self.user).id(episode_id)?.last()?{
+                episodes.push(episode);
+            }
+        }
+        Ok(episodes)
+    }
+
     pub fn tv_episode(&self) -> PyResult<Option<Episode>>{
         if self.media_type != 1{
             return Err(Error::new(ErrorKind::MediaType,"mediatype error".to_string(),&format!("media type not episode {}", self.media_type)).into())
@@ -145,16 +282,16 @@ impl Video{
     }
 
     pub fn delete(&self) -> PyResult<()>{
+        // the episodes are only found through the video, look them up before it is gone
+        let episodes = if self.media_type == 1 { self.tv_episodes()? } else { Vec::new() };
         DATABASE.delete_video(self.id)?;
         if self.media_type == 0{
             if let Some(movie) = &mut self.movie()?{
                 movie.delete()?;
             }
         }
-        else if self.media_type == 1{
-            if let Some(epiosde) = &mut self.tv_episode()?{
-                epiosde.delete()?;
-            }
+        for mut episode in episodes{
+            episode.delete()?;
         }
         Ok(())
     }
@@ -300,6 +437,8 @@ pub struct VideoResult{
     #[pyo3(get)]
     pub audios: Vec<String>,
     pub info: MediaInfo,
+    #[pyo3(get)]
+    pub start: Option<u64>,
 }
 
 #[pymethods]
@@ -358,6 +497,12 @@ impl VideoSearch{
         self.find("media_id", "=", Some(id.to_string()))
     }
 
+    // also the files covering several episodes, not only the first in media_id
+    pub fn episode(&mut self, episode_id: u64) -> PyResult<VideoSearch>{
+        self.find("(SELECT ','||GROUP_CONCAT(episode_id)||',' FROM EpisodeVideosView WHERE EpisodeVideosView.video_id = VideosView.id)",
+                  "LIKE", Some(format!("%,{},%", episode_id)))
+    }
+
     pub fn id(&mut self, id: u64) -> PyResult<VideoSearch>{
         self.find("id", "=", Some(id.to_string()))
     }
@@ -0,0 +1,165 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CacheKind{
+    Movie,
+    Tv,
+    Season,
+    Episode,
+    Person,
+    Search,
+    Collection,
+}
+
+impl CacheKind{
+    pub fn name(&self) -> &'static str{
+        match self{
+            CacheKind::Movie => "movie",
+            CacheKind::Tv => "tv",
+            CacheKind::Season => "season",
+            CacheKind::Episode => "episode",
+            CacheKind::Person => "person",
+            CacheKind::Search => "search",
+            CacheKind::Collection => "collection",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<CacheKind>{
+        match name{
+            "movie" => Some(CacheKind::Movie),
+            "tv" => Some(CacheKind::Tv),
+            "season" => Some(CacheKind::Season),
+            "episode" => Some(CacheKind::Episode),
+            "person" => Some(CacheKind::Person),
+            "search" => Some(CacheKind::Search),
+            "collection" => Some(CacheKind::Collection),
+            _ => None,
+        }
+    }
+
+    pub fn all() -> [CacheKind; 7]{
+        [CacheKind::Movie, CacheKind::Tv, CacheKind::Season, CacheKind::Episode, CacheKind::Person, CacheKind::Search, CacheKind::Collection]
+    }
+}
+
+pub struct CacheConfig{
+    pub path: Option<PathBuf>,
+    pub movie_ttl: Duration,
+    pub tv_ttl: Duration,
+    pub season_ttl: Duration,
+    pub episode_ttl: Duration,
+    pub person_ttl: Duration,
+    pub search_ttl: Duration,
+    pub collection_ttl: Duration,
+}
+
+impl CacheConfig{
+    pub fn ttl(&self, kind: CacheKind) -> Duration{
+        match kind{
+            CacheKind::Movie => self.movie_ttl,
+            CacheKind::Tv => self.tv_ttl,
+            CacheKind::Season => self.season_ttl,
+            CacheKind::Episode => self.episode_ttl,
+            CacheKind::Person => self.person_ttl,
+            CacheKind::Search => self.search_ttl,
+            CacheKind::Collection => self.collection_ttl,
+        }
+    }
+}
+
+const DAY: u64 = 24 * 60 * 60;
+
+lazy_static! {
+    pub static ref CACHE: Arc<Mutex<CacheConfig>> = Arc::new(Mutex::new(CacheConfig{
+        path: None,
+        movie_ttl: Duration::from_secs(7 * DAY),
+        tv_ttl: Duration::from_secs(DAY),
+        season_ttl: Duration::from_secs(DAY),
+        episode_ttl: Duration::from_secs(7 * DAY),
+        person_ttl: Duration::from_secs(30 * DAY),
+        search_ttl: Duration::from_secs(DAY),
+        collection_ttl: Duration::from_secs(7 * DAY),
+    }));
+}
+
+pub fn set_cache_path(path: &str){
+    CACHE.lock().unwrap().path = Some(PathBuf::from(path));
+}
+
+pub fn set_ttl(kind: CacheKind, ttl: Duration){
+    let mut cache = CACHE.lock().unwrap();
+    match kind{
+        CacheKind::Movie => cache.movie_ttl = ttl,
+        CacheKind::Tv => cache.tv_ttl = ttl,
+        CacheKind::Season => cache.season_ttl = ttl,
+        CacheKind::Episode => cache.episode_ttl = ttl,
+        CacheKind::Person => cache.person_ttl = ttl,
+        CacheKind::Search => cache.search_ttl = ttl,
+        CacheKind::Collection => cache.collection_ttl = ttl,
+    }
+}
+
+// keys are "<id>-<rest>" so that every response of one item can be dropped by id,
+// the rest hashes the query so that another append_to_response never reads an older entry
+pub fn key(id: impl std::fmt::Display, rest: &str) -> String{
+    format!("{}-{}", id, rest)
+}
+
+// FNV-1a, unlike DefaultHasher it gives the same value on every toolchain
+pub fn stable_hash(value: &str) -> u64{
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+pub fn hash_key(value: &str) -> String{
+    format!("q-{:016x}", stable_hash(value))
+}
+
+fn entry_path(kind: CacheKind, key: &str) -> Option<PathBuf>{
+    let cache = CACHE.lock().unwrap();
+    cache.path.as_ref().map(|path| path.join(kind.name()).join(format!("{}.json", key)))
+}
+
+pub fn load(kind: CacheKind, key: &str) -> Option<String>{
+    let path = entry_path(kind, key)?;
+    let ttl = CACHE.lock().unwrap().ttl(kind);
+    let age = fs::metadata(&path).ok()?.modified().ok()?.elapsed().ok()?;
+    if age > ttl{
+        return None
+    }
+    fs::read_to_string(&path).ok()
+}
+
+pub fn store(kind: CacheKind, key: &str, body: &str){
+    if let Some(path) = entry_path(kind, key){
+        if let Some(parent) = path.parent(){
+            if fs::create_dir_all(parent).is_err(){
+                return
+            }
+        }
+        let _ = fs::write(&path, body);
+    }
+}
+
+pub fn invalidate(kind: CacheKind, id: Option<u64>){
+    let dir = match CACHE.lock().unwrap().path.as_ref(){
+        Some(path) => path.join(kind.name()),
+        None => return,
+    };
+    let entries = match fs::read_dir(&dir){
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    let prefix = id.map(|id| format!("{}-", id));
+    for entry in entries.flatten(){
+        let name = entry.file_name().to_string_lossy().to_string();
+        if let Some(prefix) = &prefix{
+            if !name.starts_with(prefix){
+                continue
+            }
+        }
+        let _ = fs::remove_file(entry.path());
+    }
+}
@@ -0,0 +1,22 @@
+use std::collections::HashSet;
+
+use super::{Error, API_URL, request};
+use super::model::{Change, SearchResult};
+
+// tmdb only keeps the change feeds of the last 14 days
+pub const CHANGE_WINDOW_DAYS: u64 = 14;
+
+pub fn changes(kind: &str, start_date: &str) -> Result<HashSet<u64>, Error>{
+    let mut ids = HashSet::new();
+    let mut page = 1;
+    loop{
+        let url = format!("{}/{}/changes?start_date={}&page={}", *API_URL.lock().unwrap(), kind, start_date, page);
+        let result: SearchResult<Change> = request(url, None, false, &format!("tmdb.changes({} {} p{})", kind, start_date, page))?;
+        ids.extend(result.results.iter().map(|change| change.id));
+        if page >= result.total_pages{
+            break
+        }
+        page += 1;
+    }
+    Ok(ids)
+}
@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use reqwest::StatusCode;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::RETRY_AFTER;
+
+use super::{Error, ErrorKind, TMDBKEY, TMDBTOKEN};
+
+// no retry sleeps longer, whatever the backoff, attempt or Retry-After header
+const MAX_WAIT: Duration = Duration::from_secs(60);
+
+pub struct RateLimit{
+    pub max_requests: usize,
+    pub period: Duration,
+    pub max_retries: u32,
+    pub backoff: Duration,
+    sent: VecDeque<Instant>,
+}
+
+lazy_static! {
+    pub static ref CLIENT: Client = Client::new();
+    pub static ref RATE_LIMIT: Arc<Mutex<RateLimit>> = Arc::new(Mutex::new(RateLimit{
+        max_requests: 40,
+        period: Duration::from_secs(10),
+        max_retries: 3,
+        backoff: Duration::from_millis(500),
+        sent: VecDeque::new(),
+    }));
+}
+
+pub fn set_rate_limit(max_requests: usize, period: Duration){
+    let mut limit = RATE_LIMIT.lock().unwrap();
+    limit.max_requests = max_requests.max(1);
+    limit.period = period;
+}
+
+pub fn set_retries(max_retries: u32, backoff: Duration){
+    let mut limit = RATE_LIMIT.lock().unwrap();
+    limit.max_retries = max_retries;
+    limit.backoff = backoff;
+}
+
+// blocks until a slot is free in the sliding window, then takes it
+fn throttle(){
+    loop{
+        let mut limit = RATE_LIMIT.lock().unwrap();
+        let now = Instant::now();
+        let period = limit.period;
+        while let Some(sent) = limit.sent.front(){
+            if now.duration_since(*sent) < period{
+                break
+            }
+            limit.sent.pop_front();
+        }
+        if limit.sent.len() < limit.max_requests{
+            limit.sent.push_back(now);
+            return
+        }
+        let wait = period - now.duration_since(*limit.sent.front().unwrap());
+        drop(limit);
+        thread::sleep(wait);
+    }
+}
+
+// "Sun, 06 Nov 1994 08:49:37 GMT" as seconds since the epoch
+fn parse_http_date(value: &str) -> Option<u64>{
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    if parts.len() != 6 || parts[5] != "GMT"{
+        return None
+    }
+    let day: u64 = parts[1].parse().ok()?;
+    let month = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"]
+        .iter().position(|month| *month == parts[2])? as u64 + 1;
+    let year: u64 = parts[3].parse().ok()?;
+    let time: Vec<u64> = parts[4].split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    if time.len() != 3 || !(1..=31).contains(&day) || year < 1970{
+        return None
+    }
+    // days from civil, with years starting in march so the leap day comes last
+    let (year, month) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = (era * 146097 + day_of_era).checked_sub(719468)?;
+    Some(days * 86400 + time[0] * 3600 + time[1] * 60 + time[2])
+}
+
+// Retry-After is either a number of seconds or an HTTP date
+fn retry_after(response: &Response) -> Option<Duration>{
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    let wait = match value.parse::<u64>(){
+        Ok(seconds) => Duration::from_secs(seconds),
+        Err(_) => {
+            let date = Duration::from_secs(parse_http_date(value)?);
+            date.saturating_sub(SystemTime::now().duration_since(UNIX_EPOCH).ok()?)
+        }
+    };
+    Some(wait.min(MAX_WAIT))
+}
+
+// a response with a 5xx or 429 status is retried in send, an error only when the server could not be reached in time
+fn is_transient(e: &reqwest::Error) -> bool{
+    e.is_timeout() || e.is_connect()
+}
+
+// v4 token as a bearer header when set, else the v3 api_key query parameter
+fn authenticate(request: RequestBuilder) -> RequestBuilder{
+    if let Some(token) = TMDBTOKEN.lock().unwrap().as_ref(){
+        return request.bearer_auth(token)
+    }
+    request.query(&[("api_key", TMDBKEY.lock().unwrap().as_str())])
+}
+
+fn send(url: &str, location: &str, throttled: bool, authenticated: bool) -> Result<Response, Error>{
+    let (max_retries, backoff) = {
+        let limit = RATE_LIMIT.lock().unwrap();
+        (limit.max_retries, limit.backoff)
+    };
+    let mut attempt = 0;
+    loop{
+        if throttled{
+            throttle();
+        }
+        let wait = 2u32.checked_pow(attempt).and_then(|factor| backoff.checked_mul(factor)).unwrap_or(MAX_WAIT).min(MAX_WAIT);
+        let mut request = CLIENT.get(url);
+        if authenticated{
+            request = authenticate(request);
+        }
+        match request.send(){
+            Ok(response) => {
+                let status = response.status();
+                if status == StatusCode::TOO_MANY_REQUESTS{
+                    if attempt >= max_retries{
+                        return Err(Error::new(ErrorKind::RateLimited,
+                            format!("rate limited after {} retries", max_retries), location))
+                    }
+                    thread::sleep(retry_after(&response).unwrap_or(wait));
+                }
+                else if status.is_server_error() && attempt < max_retries{
+                    thread::sleep(retry_after(&response).unwrap_or(wait));
+                }
+                else{
+                    return Ok(response)
+                }
+            },
+            Err(e) => {
+                if !is_transient(&e) || attempt >= max_retries{
+                    return Err(Error::from_reqwest(e, location))
+                }
+                thread::sleep(wait);
+            }
+        }
+        attempt += 1;
+    }
+}
+
+pub fn get(url: &str, location: &str) -> Result<Response, Error>{
+    send(url, location, true, true)
+}
+
+pub fn download(url: &str, location: &str) -> Result<Response, Error>{
+    send(url, location, false, false)
+}
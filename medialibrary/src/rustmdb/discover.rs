@@ -0,0 +1,332 @@
+use std::collections::HashSet;
+
+use pyo3::prelude::*;
+
+use crate::database::DATABASE;
+
+use super::{Error, LANGUAGE, API_URL, request, cache::{self, CacheKind}};
+use super::model::{SearchMovie, SearchResult, SearchTv};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MediaKind{
+    Movie,
+    Tv,
+}
+
+impl MediaKind{
+    pub fn name(&self) -> &'static str{
+        match self{
+            MediaKind::Movie => "movie",
+            MediaKind::Tv => "tv",
+        }
+    }
+}
+
+#[pyclass]
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestion{
+    #[pyo3(get)]
+    pub media_type: String,
+    #[pyo3(get)]
+    pub id: u64,
+    #[pyo3(get)]
+    pub title: String,
+    #[pyo3(get)]
+    pub original_title: String,
+    #[pyo3(get)]
+    pub original_language: String,
+    #[pyo3(get)]
+    pub overview: String,
+    #[pyo3(get)]
+    pub release_date: String,
+    #[pyo3(get)]
+    pub genre_ids: Vec<u16>,
+    #[pyo3(get)]
+    pub poster_path: String,
+    #[pyo3(get)]
+    pub backdrop_path: String,
+    #[pyo3(get)]
+    pub popularity: f64,
+    #[pyo3(get)]
+    pub vote_count: u64,
+    #[pyo3(get)]
+    pub vote_average: f64,
+    #[pyo3(get)]
+    pub owned: bool,
+}
+
+impl From<SearchMovie> for Suggestion{
+    fn from(movie: SearchMovie) -> Suggestion{
+        Suggestion{
+            media_type: MediaKind::Movie.name().to_string(),
+            id: movie.id,
+            title: movie.title,
+            original_title: movie.original_title,
+            original_language: movie.original_language,
+            overview: movie.overview.unwrap_or_default(),
+            release_date: movie.release_date.unwrap_or_default(),
+            genre_ids: movie.genre_ids,
+            poster_path: movie.poster_path.unwrap_or_default(),
+            backdrop_path: movie.backdrop_path.unwrap_or_default(),
+            popularity: movie.popularity,
+            vote_count: movie.vote_count,
+            vote_average: movie.vote_average,
+            owned: false,
+        }
+    }
+}
+
+impl From<SearchTv> for Suggestion{
+    fn from(tv: SearchTv) -> Suggestion{
+        Suggestion{
+            media_type: MediaKind::Tv.name().to_string(),
+            id: tv.id,
+            title: tv.name,
+            original_title: tv.original_name,
+            original_language: tv.original_language,
+            overview: tv.overview.unwrap_or_default(),
+            release_date: tv.first_air_date.unwrap_or_default(),
+            genre_ids: tv.genre_ids,
+            poster_path: tv.poster_path.unwrap_or_default(),
+            backdrop_path: tv.backdrop_path.unwrap_or_default(),
+            popularity: tv.popularity,
+            vote_count: tv.vote_count,
+            vote_average: tv.vote_average,
+            owned: false,
+        }
+    }
+}
+
+#[pymethods]
+impl Suggestion{
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+fn fetch(kind: MediaKind, path: String, parameters: String, location: &str) -> Result<Vec<Suggestion>, Error>{
+    let url = format!("{}/{}?{}", *API_URL.lock().unwrap(), path, parameters);
+    let cache_key = Some((CacheKind::Search, cache::hash_key(&format!("{}?{}", path, parameters))));
+    Ok(match kind{
+        MediaKind::Movie => {
+            let result: SearchResult<SearchMovie> = request(url, cache_key, false, location)?;
+            result.results.into_iter().map(Suggestion::from).collect()
+        },
+        MediaKind::Tv => {
+            let result: SearchResult<SearchTv> = request(url, cache_key, false, location)?;
+            result.results.into_iter().map(Suggestion::from).collect()
+        }
+    })
+}
+
+fn annotate(kind: MediaKind, mut suggestions: Vec<Suggestion>) -> PyResult<Vec<Suggestion>>{
+    let ids: Vec<u64> = suggestions.iter().map(|suggestion| suggestion.id).collect();
+    let owned: HashSet<u64> = match kind{
+        MediaKind::Movie => DATABASE.existing_movies(&ids)?,
+        MediaKind::Tv => DATABASE.existing_tvs(&ids)?,
+    };
+    for suggestion in suggestions.iter_mut(){
+        suggestion.owned = owned.contains(&suggestion.id);
+    }
+    Ok(suggestions)
+}
+
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct Related{
+    kind: MediaKind,
+    id: u64,
+    list: &'static str,
+    language: String,
+    page: u64,
+}
+
+impl Related{
+    pub fn new(kind: MediaKind, id: u64, list: &'static str) -> Related{
+        Related{
+            kind,
+            id,
+            list,
+            language: LANGUAGE.lock().unwrap().to_string(),
+            page: 1,
+        }
+    }
+
+    pub fn request(&self) -> Result<Vec<Suggestion>, Error>{
+        let parameters = format!("language={}&page={}", self.language, self.page);
+        fetch(self.kind, format!("{}/{}/{}", self.kind.name(), self.id, self.list), parameters,
+              &format!("tmdb.{}({} {})", self.list, self.kind.name(), self.id))
+    }
+}
+
+#[pymethods]
+impl Related{
+    pub fn language(&mut self, language: &str) -> PyResult<Related>{
+        self.language = language.to_string();
+        Ok(self.clone())
+    }
+
+    pub fn page(&mut self, page: u64) -> PyResult<Related>{
+        self.page = page.max(1);
+        Ok(self.clone())
+    }
+
+    pub fn results(&self) -> PyResult<Vec<Suggestion>>{
+        annotate(self.kind, self.request()?)
+    }
+
+    pub fn json_results(&self) -> PyResult<String>{
+        let list = self.results()?;
+        Ok(serde_json::to_string(&list).unwrap())
+    }
+
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
+
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct Discover{
+    kind: MediaKind,
+    language: String,
+    page: u64,
+    genres: Vec<u64>,
+    year_from: Option<u64>,
+    year_to: Option<u64>,
+    vote_average: Option<f64>,
+    vote_count: Option<u64>,
+    original_language: Option<String>,
+    sort_by: Option<String>,
+}
+
+impl Discover{
+    pub fn new(kind: MediaKind) -> Discover{
+        Discover{
+            kind,
+            language: LANGUAGE.lock().unwrap().to_string(),
+            page: 1,
+            genres: Vec::new(),
+            year_from: None,
+            year_to: None,
+            vote_average: None,
+            vote_count: None,
+            original_language: None,
+            sort_by: None,
+        }
+    }
+
+    pub fn request(&self) -> Result<Vec<Suggestion>, Error>{
+        let date = match self.kind{
+            MediaKind::Movie => "primary_release_date",
+            MediaKind::Tv => "first_air_date",
+        };
+
+        let mut parameters = format!("language={}&page={}", self.language, self.page);
+
+        if !self.genres.is_empty(){
+            parameters += "&with_genres=";
+            parameters += &self.genres.iter().map(|genre| genre.to_string()).collect::<Vec<String>>().join(",");
+        }
+
+        if let Some(year_from) = self.year_from{
+            parameters += &format!("&{}.gte={}-01-01", date, year_from);
+        }
+
+        if let Some(year_to) = self.year_to{
+            parameters += &format!("&{}.lte={}-12-31", date, year_to);
+        }
+
+        if let Some(vote_average) = self.vote_average{
+            parameters += "&vote_average.gte=";
+            parameters += &vote_average.to_string();
+        }
+
+        if let Some(vote_count) = self.vote_count{
+            parameters += "&vote_count.gte=";
+            parameters += &vote_count.to_string();
+        }
+
+        if let Some(original_language) = &self.original_language{
+            parameters += "&with_original_language=";
+            parameters += original_language;
+        }
+
+        if let Some(sort_by) = &self.sort_by{
+            parameters += "&sort_by=";
+            parameters += sort_by;
+        }
+
+        fetch(self.kind, format!("discover/{}", self.kind.name()), parameters, &format!("tmdb.discover({})", self.kind.name()))
+    }
+}
+
+#[pymethods]
+impl Discover{
+    pub fn language(&mut self, language: &str) -> PyResult<Discover>{
+        self.language = language.to_string();
+        Ok(self.clone())
+    }
+
+    pub fn page(&mut self, page: u64) -> PyResult<Discover>{
+        self.page = page.max(1);
+        Ok(self.clone())
+    }
+
+    pub fn genres(&mut self, genres: Vec<u64>) -> PyResult<Discover>{
+        self.genres = genres;
+        Ok(self.clone())
+    }
+
+    #[args(year_to = "None")]
+    pub fn years(&mut self, year_from: Option<u64>, year_to: Option<u64>) -> PyResult<Discover>{
+        self.year_from = year_from;
+        self.year_to = year_to;
+        Ok(self.clone())
+    }
+
+    pub fn vote_average(&mut self, vote_average: f64) -> PyResult<Discover>{
+        self.vote_average = Some(vote_average);
+        Ok(self.clone())
+    }
+
+    pub fn vote_count(&mut self, vote_count: u64) -> PyResult<Discover>{
+        self.vote_count = Some(vote_count);
+        Ok(self.clone())
+    }
+
+    pub fn original_language(&mut self, original_language: &str) -> PyResult<Discover>{
+        self.original_language = Some(original_language.to_string());
+        Ok(self.clone())
+    }
+
+    pub fn sort_by(&mut self, sort_by: &str) -> PyResult<Discover>{
+        self.sort_by = Some(sort_by.to_string());
+        Ok(self.clone())
+    }
+
+    pub fn results(&self) -> PyResult<Vec<Suggestion>>{
+        annotate(self.kind, self.request()?)
+    }
+
+    pub fn json_results(&self) -> PyResult<String>{
+        let list = self.results()?;
+        Ok(serde_json::to_string(&list).unwrap())
+    }
+
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
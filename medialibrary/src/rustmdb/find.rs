@@ -0,0 +1,50 @@
+use pyo3::prelude::*;
+
+use super::{Error, ErrorKind, API_URL, request};
+use super::model::FindResults;
+
+pub const EXTERNAL_SOURCES: [&str; 8] = ["imdb_id", "tvdb_id", "freebase_mid", "freebase_id", "tvrage_id",
+                                        "facebook_id", "instagram_id", "twitter_id"];
+
+pub fn find(source: &str, id: &str) -> Result<FindResults, Error>{
+    if !EXTERNAL_SOURCES.contains(&source){
+        return Err(Error::new(ErrorKind::Tmdb, format!("unknown external source {}", source), &format!("tmdb.find({}, {})", source, id)))
+    }
+    let url = format!("{}/find/{}?external_source={}", *API_URL.lock().unwrap(), id, source);
+    request(url, None, false, &format!("tmdb.find({}, {})", source, id))
+}
+
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct ExternalMatch{
+    #[pyo3(get)]
+    pub movie_ids: Vec<u64>,
+    #[pyo3(get)]
+    pub tv_ids: Vec<u64>,
+    #[pyo3(get)]
+    pub episodes: Vec<(u64, u64, u64)>,
+    #[pyo3(get)]
+    pub person_ids: Vec<u64>,
+}
+
+impl From<FindResults> for ExternalMatch{
+    fn from(results: FindResults) -> ExternalMatch{
+        ExternalMatch{
+            movie_ids: results.movie_results.iter().map(|movie| movie.id).collect(),
+            tv_ids: results.tv_results.iter().map(|tv| tv.id).collect(),
+            episodes: results.tv_episode_results.iter().map(|episode| (episode.show_id, episode.season_number, episode.episode_number)).collect(),
+            person_ids: results.person_results.iter().map(|person| person.id).collect(),
+        }
+    }
+}
+
+#[pymethods]
+impl ExternalMatch{
+    fn __str__(&self) -> PyResult<String>{
+        Ok(format!("{:?}", self))
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("{:?}", self))
+    }
+}
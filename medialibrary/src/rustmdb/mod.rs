@@ -3,29 +3,63 @@ use std::sync::{Arc, Mutex};
 
 use crate::rustmdb::model::ErrorModel;
 use pyo3::prelude::*;
+use serde::de::DeserializeOwned;
 
-use self::{model::{Movie, Person, SearchMovie, SearchTv, Tv, TvEpisode}, movie::MovieSearch, tv::TvSearch};
+use self::{cache::CacheKind, discover::{Discover, MediaKind, Related}, find::ExternalMatch, model::{AlternativeTitles, EpisodeGroup, EpisodeGroups, Movie, MovieCollection, Person, Tv, TvEpisode, TvSeason}, movie::MovieSearch, tv::TvSearch};
 
 pub mod model;
 pub mod tv;
 pub mod movie;
-
-use strsim::jaro;
+pub mod cache;
+pub mod client;
+pub mod find;
+pub mod discover;
+pub mod changes;
+pub mod title;
 
 
 lazy_static! {
     pub static ref TMDBKEY: Arc<Mutex<String>> = Arc::new(Mutex::new("".to_string()));
+    pub static ref TMDBTOKEN: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
     pub static ref LANGUAGE: Arc<Mutex<String>> = Arc::new(Mutex::new("fr".to_string()));
+    pub static ref API_URL: Arc<Mutex<String>> = Arc::new(Mutex::new("https://api.themoviedb.org/3".to_string()));
+    pub static ref IMAGE_URL: Arc<Mutex<String>> = Arc::new(Mutex::new("https://image.tmdb.org/t/p".to_string()));
 }
 
 pub fn set_api_key(api_key: &str){
     *TMDBKEY.lock().unwrap() = api_key.to_string();
 }
 
+pub fn set_token(token: Option<&str>){
+    *TMDBTOKEN.lock().unwrap() = token.filter(|token| !token.is_empty()).map(|token| token.to_string());
+}
+
 pub fn set_language(language: &str){
     *LANGUAGE.lock().unwrap() = language.to_string();
 }
 
+pub fn set_api_url(api_url: &str){
+    *API_URL.lock().unwrap() = api_url.trim_end_matches('/').to_string();
+}
+
+pub fn set_image_url(image_url: &str){
+    *IMAGE_URL.lock().unwrap() = image_url.trim_end_matches('/').to_string();
+}
+
+fn best_title(query: &str, year: Option<u64>, results: impl Iterator<Item = (u64, Vec<String>, Option<String>)>) -> Option<u64>{
+    let query = title::normalize(query);
+    let year = year.map(|year| year.to_string());
+    let mut best: Option<(f64, bool, u64)> = None;
+    for (id, titles, date) in results{
+        let score = title::title_score(&query, &titles).0;
+        let same_year = year.is_some() && date.as_ref().and_then(|date| date.get(..4)) == year.as_deref();
+        if best.is_none_or(|(best_score, best_year, _)| score > best_score || (score == best_score && same_year && !best_year)){
+            best = Some((score, same_year, id));
+        }
+    }
+    best.map(|(_, _, id)| id)
+}
+
 #[pyclass]
 pub struct Tmdb{
 }
@@ -37,65 +71,104 @@ impl Tmdb{
         Tmdb{}
     }
 
+    // the result whose title or original title is closest, the year breaks ties,
+    // Library.match_movie and Library.match_tv add the confidence and threshold
     #[staticmethod]
-    pub fn search_movie_id(title: &str, year: u64) -> PyResult<Option<u64>>{
-        let movies  = search_movie(title).year(year).request()?;
-        if movies.results.len() == 0{
-            return Ok(None)
+    #[args(year = "None")]
+    pub fn search_movie_id(title: &str, year: Option<u64>) -> PyResult<Option<u64>>{
+        let mut search = search_movie(title);
+        if let Some(year) = year{
+            search.year(year);
         }
-        
-        let mut score = 0.0;
-        let mut best: &SearchMovie = &movies.results[0];
-        for movie in &movies.results{
-            let score_original_title = jaro(title, &movie.original_title.to_lowercase());
-            if score_original_title > score  || (score_original_title == score && movie.release_date.as_ref().unwrap_or(&"0000".to_string())[..4] == year.to_string()){
-                score = score_original_title;
-                best = movie;
-            }
+        let movies = search.request()?.results.into_iter()
+            .map(|movie| (movie.id, vec![movie.title, movie.original_title], movie.release_date));
+        Ok(best_title(title, year, movies))
+    }
 
-            let score_title = jaro(title, &movie.title.to_lowercase());
-            if score_title > score || (score_title == score && movie.release_date.as_ref().unwrap_or(&"0000".to_string())[..4] == year.to_string()){
-                score = score_title;
-                best = movie;
-            }
+    #[staticmethod]
+    #[args(year = "None")]
+    pub fn search_tv_id(title: &str, year: Option<u64>) -> PyResult<Option<u64>>{
+        let mut search = search_tv(title);
+        if let Some(year) = year{
+            search.year(year);
         }
-
-        Ok(Some(best.id))
+        let tvs = search.request()?.results.into_iter()
+            .map(|tv| (tv.id, vec![tv.name, tv.original_name], tv.first_air_date));
+        Ok(best_title(title, year, tvs))
     }
 
     #[staticmethod]
-    pub fn search_tv_id(title: &str) -> PyResult<Option<u64>>{
-        let tvs  = search_tv(title).request()?;
-        if tvs.results.len() == 0{
-            return Ok(None)
+    #[args(language = "None")]
+    pub fn search_tv_json(query: &str, language: Option<&str>) -> PyResult<String>{
+        let mut search = search_tv(query);
+        if let Some(language) = language{
+            search.language(language);
         }
-        let mut score = 0.0;
-        let mut best: &SearchTv = &tvs.results[0];
-        for tv in &tvs.results{
-            let score_original_title = jaro(title, &tv.original_name.to_lowercase());
-            let score_title = jaro(title, &tv.name.to_lowercase());
-            if score_original_title > score{
-                score = score_original_title;
-                best = tv;
-            }
-            if score_title > score{
-                score = score_title;
-                best = tv;
-            }
+        let tvs  = search.request()?;
+        Ok(serde_json::to_string(&tvs).unwrap())
+    }
+
+    #[staticmethod]
+    #[args(language = "None")]
+    pub fn search_movie_json(query: &str, language: Option<&str>) -> PyResult<String>{
+        let mut search = search_movie(query);
+        if let Some(language) = language{
+            search.language(language);
         }
-        Ok(Some(best.id))
+        let movies  = search.request()?;
+        Ok(serde_json::to_string(&movies).unwrap())
     }
 
     #[staticmethod]
-    pub fn search_tv_json(query: &str) -> PyResult<String>{
-        let tvs  = search_tv(query).request()?;
-        Ok(serde_json::to_string(&tvs).unwrap())
+    pub fn find_by_external_id(source: &str, id: &str) -> PyResult<ExternalMatch>{
+        Ok(find::find(source, id)?.into())
     }
 
     #[staticmethod]
-    pub fn search_movie_json(query: &str) -> PyResult<String>{
-        let movies  = search_movie(query).request()?;
-        Ok(serde_json::to_string(&movies).unwrap())
+    pub fn movie_recommendations(id: u64) -> Related{
+        Related::new(MediaKind::Movie, id, "recommendations")
+    }
+
+    #[staticmethod]
+    pub fn movie_similar(id: u64) -> Related{
+        Related::new(MediaKind::Movie, id, "similar")
+    }
+
+    #[staticmethod]
+    pub fn tv_recommendations(id: u64) -> Related{
+        Related::new(MediaKind::Tv, id, "recommendations")
+    }
+
+    #[staticmethod]
+    pub fn tv_similar(id: u64) -> Related{
+        Related::new(MediaKind::Tv, id, "similar")
+    }
+
+    #[staticmethod]
+    pub fn discover_movie() -> Discover{
+        Discover::new(MediaKind::Movie)
+    }
+
+    #[staticmethod]
+    pub fn discover_tv() -> Discover{
+        Discover::new(MediaKind::Tv)
+    }
+
+    #[staticmethod]
+    #[args(kind = "None", id = "None")]
+    pub fn clear_cache(kind: Option<&str>, id: Option<u64>) -> PyResult<()>{
+        match kind{
+            Some(name) => match CacheKind::from_name(name){
+                Some(kind) => cache::invalidate(kind, id),
+                None => return Err(Error::new(ErrorKind::Cache, format!("unknown cache kind {}", name), "tmdb.clear_cache").into()),
+            },
+            None => {
+                for kind in CacheKind::all(){
+                    cache::invalidate(kind, id);
+                }
+            }
+        }
+        Ok(())
     }
 
 }
@@ -108,81 +181,99 @@ pub fn search_tv<'a>(title:&'a str) -> TvSearch<'a>{
     TvSearch::new(title)
 }
 
-pub fn get_movie(id: u64) -> Result<Movie, Error>{
-    let parameters = format!("api_key={}&language={}&append_to_response=credits,keywords,videos", *TMDBKEY.lock().unwrap(), *LANGUAGE.lock().unwrap());
-    let body = match reqwest::blocking::get(format!("https://api.themoviedb.org/3/movie/{}?{}",id, parameters)){
-        Ok(body) => body,
-        Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.movie({})", &id)))
-    };
-    if body.status().is_success(){
-        match body.json(){
-            Ok(movie) => return Ok(movie),
-            Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.movie({}) parse body", &id))),
-        };
-    }
-    let e: ErrorModel = match body.json(){
-        Ok(e) => e,
-        Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.movie({}) parse error", id))),
-    };
-    Err(Error::new(ErrorKind::Tmdb, e.status_message, &format!("tmdb.movie({}) return error", id)))
+pub fn get_movie(id: u64, force: bool) -> Result<Movie, Error>{
+    let language = LANGUAGE.lock().unwrap().clone();
+    let parameters = format!("language={}&append_to_response=credits,keywords,videos,translations", language);
+    let url = format!("{}/movie/{}?{}", *API_URL.lock().unwrap(), id, parameters);
+    request(url, Some((CacheKind::Movie, cache::key(id, &cache::hash_key(&parameters)))), force, &format!("tmdb.movie({})", id))
 }
 
-pub fn get_tv(id: u64) -> Result<Tv, Error>{
-    let parameters = format!("api_key={}&language={}&append_to_response=credits,keywords,videos", *TMDBKEY.lock().unwrap(), *LANGUAGE.lock().unwrap());
-    let body = match reqwest::blocking::get(format!("https://api.themoviedb.org/3/tv/{}?{}",id, parameters)){
-        Ok(body) => body,
-        Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.tv({})", id)))
-    };
-    if body.status().is_success(){
-        match body.json(){
-            Ok(movie) => return Ok(movie),
-            Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.tv({}) parse body", id))),
-        };
-    }
-    let e: ErrorModel = match body.json(){
-        Ok(e) => e,
-        Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.tv({}) parse error", id))),
-    };
-    Err(Error::new(ErrorKind::Tmdb, e.status_message, &format!("tmdb.tv({}) return error", id)))
+pub fn get_collection(id: u64, force: bool) -> Result<MovieCollection, Error>{
+    let language = LANGUAGE.lock().unwrap().clone();
+    let parameters = format!("language={}", language);
+    let url = format!("{}/collection/{}?{}", *API_URL.lock().unwrap(), id, parameters);
+    request(url, Some((CacheKind::Collection, cache::key(id, &cache::hash_key(&parameters)))), force, &format!("tmdb.collection({})", id))
 }
 
-pub fn get_tv_episode(id: u64, season: u64, episode: u64) -> Result<TvEpisode, Error>{
-    let parameters = format!("api_key={}&language={}&append_to_response=credits", *TMDBKEY.lock().unwrap(), *LANGUAGE.lock().unwrap());
-    let body = match reqwest::blocking::get(format!("https://api.themoviedb.org/3/tv/{}/season/{}/episode/{}?{}",id, season, episode, parameters)){
-        Ok(body) => body,
-        Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.episode({} s{}e{})", id, season, episode)))
-    };
-    if body.status().is_success(){
-        match body.json(){
-            Ok(movie) => return Ok(movie),
-            Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.episode({} s{}e{}) parse body", id, season, episode))),
-        };
-    }
-    let e: ErrorModel = match body.json(){
-        Ok(e) => e,
-        Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.episode({} s{}e{}) parse error", id, season, episode))),
-    };
-    Err(Error::new(ErrorKind::Tmdb, e.status_message, &format!("tmdb.episode({} s{}e{}) return error", id, season, episode)))
+pub fn get_tv(id: u64, force: bool) -> Result<Tv, Error>{
+    let language = LANGUAGE.lock().unwrap().clone();
+    let parameters = format!("language={}&append_to_response=credits,keywords,videos,external_ids,translations", language);
+    let url = format!("{}/tv/{}?{}", *API_URL.lock().unwrap(), id, parameters);
+    request(url, Some((CacheKind::Tv, cache::key(id, &cache::hash_key(&parameters)))), force, &format!("tmdb.tv({})", id))
+}
+
+pub fn get_tv_season(id: u64, season: u64, force: bool) -> Result<TvSeason, Error>{
+    let language = LANGUAGE.lock().unwrap().clone();
+    let parameters = format!("language={}&append_to_response=credits", language);
+    let url = format!("{}/tv/{}/season/{}?{}", *API_URL.lock().unwrap(), id, season, parameters);
+    request(url, Some((CacheKind::Season, cache::key(id, &format!("s{}-{}", season, cache::hash_key(&parameters))))), force,
+            &format!("tmdb.season({} s{})", id, season))
+}
 
+pub fn get_tv_episode(id: u64, season: u64, episode: u64, force: bool) -> Result<TvEpisode, Error>{
+    let language = LANGUAGE.lock().unwrap().clone();
+    let parameters = format!("language={}&append_to_response=credits,external_ids", language);
+    let url = format!("{}/tv/{}/season/{}/episode/{}?{}", *API_URL.lock().unwrap(), id, season, episode, parameters);
+    request(url, Some((CacheKind::Episode, cache::key(id, &format!("s{}e{}-{}", season, episode, cache::hash_key(&parameters))))), force,
+            &format!("tmdb.episode({} s{}e{})", id, season, episode))
 }
 
-pub fn get_person(id: u64) -> Result<Person, Error>{
-    let parameters = format!("api_key={}&language={}", *TMDBKEY.lock().unwrap(), *LANGUAGE.lock().unwrap());
-    let body = match reqwest::blocking::get(format!("https://api.themoviedb.org/3/person/{}?{}",id, parameters)){
-        Ok(body) => body,
-        Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.person({})", id)))
+pub fn get_person(id: u64, force: bool) -> Result<Person, Error>{
+    let language = LANGUAGE.lock().unwrap().clone();
+    let parameters = format!("language={}&append_to_response=combined_credits", language);
+    let url = format!("{}/person/{}?{}", *API_URL.lock().unwrap(), id, parameters);
+    request(url, Some((CacheKind::Person, cache::key(id, &cache::hash_key(&parameters)))), force, &format!("tmdb.person({})", id))
+}
+
+pub fn get_alternative_titles(kind: MediaKind, id: u64, force: bool) -> Result<AlternativeTitles, Error>{
+    let cache_kind = match kind{
+        MediaKind::Movie => CacheKind::Movie,
+        MediaKind::Tv => CacheKind::Tv,
     };
+    let url = format!("{}/{}/{}/alternative_titles", *API_URL.lock().unwrap(), kind.name(), id);
+    request(url, Some((cache_kind, cache::key(id, "alternative_titles"))), force, &format!("tmdb.alternative_titles({} {})", kind.name(), id))
+}
+
+pub fn get_episode_groups(tv_id: u64, force: bool) -> Result<EpisodeGroups, Error>{
+    let url = format!("{}/tv/{}/episode_groups", *API_URL.lock().unwrap(), tv_id);
+    request(url, Some((CacheKind::Tv, cache::key(tv_id, "episode_groups"))), force, &format!("tmdb.episode_groups({})", tv_id))
+}
+
+pub fn get_episode_group(tv_id: u64, group_id: &str, force: bool) -> Result<EpisodeGroup, Error>{
+    let url = format!("{}/tv/episode_group/{}", *API_URL.lock().unwrap(), group_id);
+    // keyed under the tv id so that invalidating the show also drops its groups
+    request(url, Some((CacheKind::Tv, cache::key(tv_id, &format!("episode_group-{}", group_id)))), force, &format!("tmdb.episode_group({} {})", tv_id, group_id))
+}
+
+pub fn request<T: DeserializeOwned>(url: String, cache_key: Option<(CacheKind, String)>, force: bool, location: &str) -> Result<T, Error>{
+    // force skips the stored copy but still refreshes it
+    if let Some((kind, key)) = cache_key.as_ref().filter(|_| !force){
+        if let Some(text) = cache::load(*kind, key){
+            if let Ok(value) = serde_json::from_str(&text){
+                return Ok(value)
+            }
+        }
+    }
+    let body = client::get(&url, location)?;
     if body.status().is_success(){
-        match body.json(){
-            Ok(movie) => return Ok(movie),
-            Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.person({}) parse body", id))),
+        let text = match body.text(){
+            Ok(text) => text,
+            Err(e) => return Err(Error::from_reqwest(e, &format!("{} read body", location))),
+        };
+        let value = match serde_json::from_str(&text){
+            Ok(value) => value,
+            Err(e) => return Err(Error::new(ErrorKind::Json, e.to_string(), &format!("{} parse body", location))),
         };
+        if let Some((kind, key)) = &cache_key{
+            cache::store(*kind, key, &text);
+        }
+        return Ok(value)
     }
     let e: ErrorModel = match body.json(){
         Ok(e) => e,
-        Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.person({}) parse error", id))),
+        Err(e) => return Err(Error::from_reqwest(e, &format!("{} parse error", location))),
     };
-    Err(Error::new(ErrorKind::Tmdb, e.status_message, &format!("tmdb.person({}) return error", id)))
+    Err(Error::new(ErrorKind::Tmdb, e.status_message, &format!("{} return error", location)))
 }
 
 #[derive(Debug)]
@@ -192,6 +283,8 @@ pub enum ErrorKind{
     Json,
     ReqwestError,
     Tmdb,
+    Cache,
+    RateLimited,
 }
 
 #[derive(Debug)]
@@ -202,6 +295,10 @@ pub struct Error{
 }
 
 impl Error{
+    pub fn kind(&self) -> &ErrorKind{
+        &self.kind
+    }
+
     pub fn new(kind: ErrorKind, description: String, location: &str) -> Error{
         Error{
             kind,
@@ -211,6 +308,7 @@ impl Error{
     }
 
     pub fn from_reqwest(e: reqwest::Error, location: &str) -> Error{
+        let e = e.without_url();
         if e.is_timeout(){
             Error::new(ErrorKind::Timeout, e.to_string(), location)
         }
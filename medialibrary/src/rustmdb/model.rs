@@ -24,6 +24,59 @@ pub struct Person {
     pub adult: bool,
     pub imdb_id: Option<String>,
     pub homepage: Option<String>,
+    #[serde(default)]
+    pub combined_credits: CombinedCredits,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+pub struct CombinedCredits {
+    pub cast: Vec<CombinedCredit>,
+    pub crew: Vec<CombinedCredit>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct CombinedCredit {
+    pub id: u64,
+    pub media_type: String,
+    pub credit_id: String,
+    pub title: Option<String>,
+    pub name: Option<String>,
+    pub original_title: Option<String>,
+    pub original_name: Option<String>,
+    pub character: Option<String>,
+    pub job: Option<String>,
+    pub department: Option<String>,
+    pub release_date: Option<String>,
+    pub first_air_date: Option<String>,
+    pub poster_path: Option<String>,
+    pub episode_count: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+pub struct ExternalIds {
+    pub imdb_id: Option<String>,
+    pub tvdb_id: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct FindId {
+    pub id: u64,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct FindEpisode {
+    pub id: u64,
+    pub show_id: u64,
+    pub season_number: u64,
+    pub episode_number: u64,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct FindResults {
+    pub movie_results: Vec<FindId>,
+    pub tv_results: Vec<FindId>,
+    pub tv_episode_results: Vec<FindEpisode>,
+    pub person_results: Vec<FindId>,
 }
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
@@ -34,6 +87,12 @@ pub struct SearchResult<T>{
     pub results: Vec<T>,
 }
 
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Change{
+    pub id: u64,
+    pub adult: Option<bool>,
+}
+
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct SearchMovie {
     pub id: u64,
@@ -77,7 +136,7 @@ pub struct Language {
     pub name: String,
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
 pub struct Cast {
     pub adult: bool,
     pub gender: Option<u8>,
@@ -93,7 +152,7 @@ pub struct Cast {
     pub order: u64,
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
 pub struct Crew {
     pub adult: bool,
     pub gender: Option<u8>,
@@ -108,7 +167,7 @@ pub struct Crew {
     pub job: String,
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
 pub struct Credits {
     pub cast: Vec<Cast>,
     pub crew: Vec<Crew>,
@@ -147,10 +206,58 @@ pub struct Videos {
     pub results: Vec<Video>,
 }
 
+#[derive(Debug, PartialEq, Deserialize, Serialize, Default)]
+pub struct MovieTranslation {
+    pub title: Option<String>,
+    pub overview: Option<String>,
+    pub tagline: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Default)]
+pub struct TvTranslation {
+    pub name: Option<String>,
+    pub overview: Option<String>,
+    pub tagline: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Translation<T> {
+    pub iso_3166_1: String,
+    pub iso_639_1: String,
+    pub name: String,
+    pub english_name: String,
+    pub data: T,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Translations<T> {
+    pub translations: Vec<Translation<T>>,
+}
+
+impl<T> Default for Translations<T>{
+    fn default() -> Self{
+        Translations{ translations: Vec::new() }
+    }
+}
+
+impl<T> Translations<T>{
+    // no languages configured keeps every translation
+    pub fn retain_languages(&mut self, languages: &[String]){
+        if languages.is_empty(){
+            return
+        }
+        self.translations.retain(|translation| languages.contains(&translation.iso_639_1));
+    }
+}
+
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct Movie {
     pub id: u64,
     pub budget: u64,
+    #[serde(default)]
+    pub revenue: u64,
+    #[serde(default)]
+    pub runtime: Option<u64>,
     pub poster_path: Option<String>,
     pub backdrop_path: Option<String>,
     pub homepage: Option<String>,
@@ -172,6 +279,36 @@ pub struct Movie {
     pub credits: Credits,
     pub videos: Videos,
     pub keywords: KeywordsMovie,
+    pub imdb_id: Option<String>,
+    pub belongs_to_collection: Option<BelongsToCollection>,
+    #[serde(default)]
+    pub translations: Translations<MovieTranslation>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct BelongsToCollection {
+    pub id: u64,
+    pub name: String,
+    pub poster_path: Option<String>,
+    pub backdrop_path: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct MovieCollection {
+    pub id: u64,
+    pub name: String,
+    pub overview: Option<String>,
+    pub poster_path: Option<String>,
+    pub backdrop_path: Option<String>,
+    pub parts: Vec<MovieCollectionPart>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct MovieCollectionPart {
+    pub id: u64,
+    pub title: String,
+    pub release_date: Option<String>,
+    pub poster_path: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
@@ -251,10 +388,14 @@ pub struct Tv {
     pub seasons: Vec<Season>,
     pub videos: Videos,
     pub keywords: KeywordsTv,
+    #[serde(default)]
+    pub external_ids: ExternalIds,
+    #[serde(default)]
+    pub translations: Translations<TvTranslation>,
 }
 
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
 pub struct GuestStar{
     pub id: u64,
     pub name: String,
@@ -278,6 +419,118 @@ pub struct TvEpisode{
     pub vote_average: f64,
     pub vote_count: u64,
     pub credits: Credits,
+    #[serde(default)]
+    pub external_ids: ExternalIds,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct SeasonEpisode{
+    pub air_date: Option<String>,
+    pub episode_number: u64,
+    pub id: u64,
+    pub name: String,
+    pub overview: Option<String>,
+    pub production_code: Option<String>,
+    pub season_number: u64,
+    pub still_path: Option<String>,
+    pub vote_average: f64,
+    pub vote_count: u64,
+    pub crew: Vec<Crew>,
+    pub guest_stars: Vec<GuestStar>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct TvSeason{
+    pub id: u64,
+    pub air_date: Option<String>,
+    pub name: String,
+    pub overview: Option<String>,
+    pub poster_path: Option<String>,
+    pub season_number: u64,
+    pub episodes: Vec<SeasonEpisode>,
+    pub credits: Credits,
+}
+
+impl TvSeason{
+    // a season only lists the crew and guests of its episodes, their cast and external ids need the episode itself
+    pub fn tv_episodes(&self) -> Vec<TvEpisode>{
+        self.episodes.iter().map(|episode| TvEpisode{
+            air_date: episode.air_date.clone().unwrap_or_default(),
+            guest_stars: episode.guest_stars.clone(),
+            name: episode.name.clone(),
+            overview: episode.overview.clone(),
+            id: episode.id,
+            production_code: episode.production_code.clone(),
+            season_number: episode.season_number,
+            episode_number: episode.episode_number,
+            still_path: episode.still_path.clone(),
+            vote_average: episode.vote_average,
+            vote_count: episode.vote_count,
+            credits: Credits{
+                cast: Vec::new(),
+                crew: episode.crew.clone(),
+            },
+            external_ids: ExternalIds::default(),
+        }).collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct AlternativeTitle {
+    #[serde(default)]
+    pub iso_3166_1: String,
+    pub title: String,
+}
+
+// movies list them under "titles", tvs under "results"
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct AlternativeTitles {
+    #[serde(default, alias = "results")]
+    pub titles: Vec<AlternativeTitle>,
+}
+
+// type 2 is the absolute order, the one used for anime numbering
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct EpisodeGroupSummary {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub group_type: u64,
+    #[serde(default)]
+    pub episode_count: u64,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct EpisodeGroups {
+    #[serde(default)]
+    pub results: Vec<EpisodeGroupSummary>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct EpisodeGroupEpisode {
+    pub season_number: u64,
+    pub episode_number: u64,
+    #[serde(default)]
+    pub order: u64,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct EpisodeGroupPart {
+    pub name: String,
+    #[serde(default)]
+    pub order: u64,
+    #[serde(default)]
+    pub episodes: Vec<EpisodeGroupEpisode>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct EpisodeGroup {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub group_type: u64,
+    #[serde(default)]
+    pub groups: Vec<EpisodeGroupPart>,
 }
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
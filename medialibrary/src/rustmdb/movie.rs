@@ -1,9 +1,8 @@
-use super::{Error, ErrorKind, TMDBKEY, LANGUAGE};
+use super::{Error, LANGUAGE, API_URL, request, cache::{self, CacheKind}};
 
-use super::model::{SearchMovie, SearchResult, ErrorModel};
+use super::model::{SearchMovie, SearchResult};
 
 pub struct MovieSearch <'a>{
-    api_key: String,
     language: String,
     query: &'a str,
     page: u64,
@@ -16,7 +15,6 @@ pub struct MovieSearch <'a>{
 impl <'a>MovieSearch<'a> {
     pub fn new(query: &'a str,) -> MovieSearch<'a>{
         MovieSearch{
-            api_key: TMDBKEY.lock().unwrap().to_string(),
             language: LANGUAGE.lock().unwrap().to_string(),
             query,
             page: 1,
@@ -33,7 +31,6 @@ impl <'a>MovieSearch<'a> {
         self
     }
 
-    #[allow(dead_code)]
     pub fn language(&mut self, language: &str) -> &mut MovieSearch<'a>{
         self.language = language.to_string();
         self
@@ -66,7 +63,7 @@ impl <'a>MovieSearch<'a> {
 
     pub fn request(&self) -> Result<SearchResult<SearchMovie>, Error>{
 
-        let mut parameters = format!("api_key={}&query={}&page={}&language={}", self.api_key, self.query, self.page, self.language);
+        let mut parameters = format!("query={}&page={}&language={}", self.query, self.page, self.language);
 
         if let Some(region) = self.region{
             parameters += "&region=";
@@ -88,20 +85,7 @@ impl <'a>MovieSearch<'a> {
             parameters += &primary_release_year.to_string();
         }
 
-        let body = match reqwest::blocking::get(format!("https://api.themoviedb.org/3/search/movie?{}",parameters)){
-            Ok(body) => body,
-            Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.SearchMovie({})", self.query)))
-        };
-        if body.status().is_success(){
-            match body.json(){
-                Ok(movie) => return Ok(movie),
-                Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.SearchMovie({}) parse body",  self.query))),
-            };
-        }
-        let e: ErrorModel = match body.json(){
-            Ok(e) => e,
-            Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.SearchMovie({}) parse error",  self.query))),
-        };
-        Err(Error::new(ErrorKind::Tmdb, e.status_message, &format!("tmdb.SearchMovie({})",  self.query)))
+        let url = format!("{}/search/movie?{}", *API_URL.lock().unwrap(), parameters);
+        request(url, Some((CacheKind::Search, cache::hash_key(&format!("movie?{}", parameters)))), false, &format!("tmdb.SearchMovie({})", self.query))
     }
 }
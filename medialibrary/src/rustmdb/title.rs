@@ -0,0 +1,50 @@
+use strsim::jaro;
+
+const ARTICLES: [&str; 9] = ["the", "a", "an", "le", "la", "les", "l", "der", "die"];
+
+fn fold(c: char) -> char{
+    match c{
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+        'ç' => 'c',
+        'è' | 'é' | 'ê' | 'ë' => 'e',
+        'ì' | 'í' | 'î' | 'ï' => 'i',
+        'ñ' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => 'o',
+        'ù' | 'ú' | 'û' | 'ü' => 'u',
+        'ý' | 'ÿ' => 'y',
+        _ => c,
+    }
+}
+
+// lowercase, without accents, punctuation or a leading article
+pub fn normalize(title: &str) -> String{
+    let title: String = title.to_lowercase().replace('&', " and ").chars()
+        .map(|c| if c.is_alphanumeric(){ fold(c) } else { ' ' })
+        .collect();
+    let mut words: Vec<&str> = title.split_whitespace().collect();
+    if words.len() > 1 && ARTICLES.contains(&words[0]){
+        words.remove(0);
+    }
+    words.join(" ")
+}
+
+// strsim 0.10 does not cap the common prefix at 4, "fight club" would score 1.0 against "fight club 2"
+fn jaro_winkler(a: &str, b: &str) -> f64{
+    let similarity = jaro(a, b);
+    let prefix = a.chars().zip(b.chars()).take(4).take_while(|(x, y)| x == y).count();
+    similarity + 0.1 * prefix as f64 * (1.0 - similarity)
+}
+
+pub fn title_score(query: &str, titles: &[String]) -> (f64, String){
+    let mut best = (0.0, String::new());
+    for title in titles{
+        let score = match normalize(title){
+            normalized if normalized == query => 1.0,
+            normalized => jaro_winkler(query, &normalized),
+        };
+        if score > best.0{
+            best = (score, title.clone());
+        }
+    }
+    best
+}
@@ -1,7 +1,6 @@
-use super::{Error, ErrorKind, TMDBKEY, LANGUAGE, model::{SearchResult, SearchTv, ErrorModel}};
+use super::{Error, LANGUAGE, API_URL, request, cache::{self, CacheKind}, model::{SearchResult, SearchTv}};
 
 pub struct TvSearch <'a>{
-    api_key: String,
     language: String,
     query: &'a str,
     page: u64,
@@ -12,7 +11,6 @@ pub struct TvSearch <'a>{
 impl <'a>TvSearch<'a> {
     pub fn new(query: &'a str) -> TvSearch<'a>{
         TvSearch{
-            api_key: TMDBKEY.lock().unwrap().to_string(),
             language: LANGUAGE.lock().unwrap().to_string(),
             query,
             page: 1,
@@ -26,12 +24,16 @@ impl <'a>TvSearch<'a> {
         self
     }
 
-    #[allow(dead_code)]
     pub fn language(&mut self, language: &str) -> &mut TvSearch<'a>{
         self.language = language.to_string();
         self
     }
 
+    pub fn year(&mut self, year: u64) -> &mut TvSearch<'a>{
+        self.first_air_date_year = Some(year);
+        self
+    }
+
     #[allow(dead_code)]
     pub fn include_adult(&mut self, include_adult: bool) -> &mut TvSearch<'a>{
         self.include_adult = Some(include_adult);
@@ -41,7 +43,7 @@ impl <'a>TvSearch<'a> {
     #[allow(dead_code)]
     pub fn request(&self) -> Result<SearchResult<SearchTv>, Error>{
 
-        let mut parameters = format!("api_key={}&query={}&page={}&language={}", self.api_key, self.query, self.page, self.language);
+        let mut parameters = format!("query={}&page={}&language={}", self.query, self.page, self.language);
 
         if let Some(include_adult) = self.include_adult{
             parameters += "&include_adult=";
@@ -52,20 +54,7 @@ impl <'a>TvSearch<'a> {
             parameters += "&first_air_date_year=";
             parameters += &first_air_date_year.to_string();
         }
-        let body = match reqwest::blocking::get(format!("https://api.themoviedb.org/3/search/tv?{}",parameters)){
-            Ok(body) => body,
-            Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.SearchTv({})", self.query)))
-        };
-        if body.status().is_success(){
-            match body.json(){
-                Ok(movie) => return Ok(movie),
-                Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.SearchTv({}) parse body",  self.query))),
-            };
-        }
-        let e: ErrorModel = match body.json(){
-            Ok(e) => e,
-            Err(e) => return Err(Error::from_reqwest(e, &format!("tmdb.SearchTv({}) parse error",  self.query))),
-        };
-        Err(Error::new(ErrorKind::Tmdb, e.status_message, &format!("tmdb.SearchTv({})",  self.query)))
+        let url = format!("{}/search/tv?{}", *API_URL.lock().unwrap(), parameters);
+        request(url, Some((CacheKind::Search, cache::hash_key(&format!("tv?{}", parameters)))), false, &format!("tmdb.SearchTv({})", self.query))
     }
 }
\ No newline at end of file
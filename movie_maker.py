@@ -1,5 +1,5 @@
 import medialibrary
-from medialibrary import Library, Tmdb
+from medialibrary import Library
 import toml
 
 config = toml.load("./library_config.toml")
@@ -14,16 +14,34 @@ tv_id_cache = {}
 for video in lib.videos(user).tv().unknown().results():
     video = video.full()
     try:
-        title, season, episode = video.parse_tv()
+        release = video.parse()
+        title = release.title
         if title in tv_id_cache:
             tv_id = tv_id_cache[title]
         else:
-            tv_id = Tmdb.search_tv_id(title)
+            tv_id = lib.match_tv(title).accepted
             tv_id_cache[title] = tv_id
         if tv_id is None:
             print(f"Tv id not found: {title}")
             continue
-        video.set_tv(tv_id, season, episode)
+        mapping = None
+        if release.date is not None:
+            mapping = video.resolve_date(tv_id)
+            print(f"aired {mapping.date} => S{mapping.season}E{mapping.episode} ({mapping.source})")
+        # an explicit season, from an SxxEyy token, a three digit number or a season folder, wins over absolute numbering
+        elif release.absolute is not None and release.season is None:
+            try:
+                mapping = video.resolve_absolute(tv_id)
+                print(f"absolute {mapping.absolute} => S{mapping.season}E{mapping.episode} ({mapping.source})")
+            except medialibrary.LibraryError:
+                mapping = None
+        if mapping is not None:
+            video.set_tv(tv_id, mapping.season, mapping.episode)
+        else:
+            season = release.season
+            if season is None:
+                _, season, _ = video.parse_tv()
+            video.set_tv_episodes(tv_id, season, release.episodes)
         print(f"edit episode {video.path} {tv_id}")
     except Exception as e:
         print(f"Error: {video.path} => {e}")
@@ -33,7 +51,7 @@ for video in lib.videos(user).movie().unknown().results():
     video = video.full()
     try:
         title, year = video.parse_movie()
-        movie_id = Tmdb.search_movie_id(title, year)
+        movie_id = lib.match_movie(title, year).accepted
         if movie_id is None:
             print(video.path)
             print(f"regex: {title}, {year}")